@@ -0,0 +1,60 @@
+//! Interleaved batch inversion against `ark_ff::batch_inversion`, run with `cargo bench`.
+//! Prints the best of several runs of each, single threaded unless built with `parallel`.
+
+use std::{hint::black_box, time::Instant};
+
+use ark_bn254::Fr;
+use ark_ff::{batch_inversion as ark_batch_inversion, Field};
+use ark_std::test_rng;
+use fast_eval::pointwise;
+
+#[allow(dead_code)]
+#[path = "../src/test_fields.rs"]
+mod test_fields;
+
+use test_fields::BabyBear;
+
+const RUNS: usize = 15;
+
+fn best_of<F: Field>(name: &str, elements: &[F], inversion: fn(&mut [F])) {
+    let mut best = u128::MAX;
+    for _ in 0..RUNS {
+        let mut v = elements.to_vec();
+        let start = Instant::now();
+        inversion(black_box(&mut v));
+        best = best.min(start.elapsed().as_micros());
+        black_box(v);
+    }
+    println!("{:<40} {:>8}us", name, best);
+}
+
+fn random_elements<F: Field>(n: usize) -> Vec<F> {
+    let mut rng = test_rng();
+    (0..n).map(|_| F::rand(&mut rng)).collect()
+}
+
+fn main() {
+    let babybear = random_elements::<BabyBear>(1 << 20);
+    best_of(
+        "BabyBear 2^20 ark_ff::batch_inversion",
+        &babybear,
+        ark_batch_inversion,
+    );
+    best_of(
+        "BabyBear 2^20 pointwise::batch_inversion",
+        &babybear,
+        pointwise::batch_inversion,
+    );
+
+    let bn254 = random_elements::<Fr>(1 << 18);
+    best_of(
+        "BN254 2^18 ark_ff::batch_inversion",
+        &bn254,
+        ark_batch_inversion,
+    );
+    best_of(
+        "BN254 2^18 pointwise::batch_inversion",
+        &bn254,
+        pointwise::batch_inversion,
+    );
+}
@@ -0,0 +1,157 @@
+use std::{collections::VecDeque, sync::Mutex};
+
+use ark_ff::FftField;
+use ark_poly::univariate::DensePolynomial;
+
+use crate::{PolyProcessor, WeightMode};
+
+/// Wraps a processor and memoizes `batch_evaluate_lagrange_basis` for the last `capacity`
+/// distinct points, evicting the least recently used one.
+/// Meant for the handful of challenge points which get reused across many proofs,
+/// lookups are linear in `capacity`
+pub struct LagrangeCache<F: FftField> {
+    inner: Box<dyn PolyProcessor<F>>,
+    capacity: usize,
+    // most recently used first
+    entries: Mutex<VecDeque<(F, Vec<F>)>>,
+}
+
+impl<F: FftField> LagrangeCache<F> {
+    pub fn new(inner: Box<dyn PolyProcessor<F>>, capacity: usize) -> Self {
+        Self {
+            inner,
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Points currently cached, most recently used first
+    pub fn cached_points(&self) -> Vec<F> {
+        let entries = self.entries.lock().unwrap();
+        entries.iter().map(|(point, _)| *point).collect()
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    pub fn into_inner(self) -> Box<dyn PolyProcessor<F>> {
+        self.inner
+    }
+}
+
+impl<F: FftField> PolyProcessor<F> for LagrangeCache<F> {
+    fn size(&self) -> usize {
+        self.inner.size()
+    }
+
+    fn elements(&self) -> Vec<F> {
+        self.inner.elements()
+    }
+
+    fn get_vanishing(&self) -> DensePolynomial<F> {
+        self.inner.get_vanishing()
+    }
+
+    fn evaluate_vanishing_at(&self, point: &F) -> F {
+        self.inner.evaluate_vanishing_at(point)
+    }
+
+    fn get_ri(&self) -> Vec<F> {
+        self.inner.get_ri()
+    }
+
+    fn get_vanishing_derivative_evals(&self) -> Vec<F> {
+        self.inner.get_vanishing_derivative_evals()
+    }
+
+    fn evaluate_over_domain(&self, f: &DensePolynomial<F>) -> Vec<F> {
+        self.inner.evaluate_over_domain(f)
+    }
+
+    fn evaluate_derivatives_over_domain(&self, f: &DensePolynomial<F>, k: usize) -> Vec<Vec<F>> {
+        self.inner.evaluate_derivatives_over_domain(f, k)
+    }
+
+    fn interpolate(&self, evals: &[F]) -> DensePolynomial<F> {
+        self.inner.interpolate(evals)
+    }
+
+    fn reduce_mod_vanishing(&self, f: &DensePolynomial<F>) -> DensePolynomial<F> {
+        self.inner.reduce_mod_vanishing(f)
+    }
+
+    fn weighted_sum(&self, f: &DensePolynomial<F>, weights: &[F]) -> F {
+        self.inner.weighted_sum(f, weights)
+    }
+
+    fn interpolate_weighted(
+        &self,
+        evals: &[F],
+        weights: &[F],
+        mode: WeightMode,
+    ) -> DensePolynomial<F> {
+        self.inner.interpolate_weighted(evals, weights, mode)
+    }
+
+    fn batch_evaluate_lagrange_basis(&self, point: &F) -> Vec<F> {
+        if self.capacity == 0 {
+            return self.inner.batch_evaluate_lagrange_basis(point);
+        }
+
+        {
+            let mut entries = self.entries.lock().unwrap();
+            if let Some(position) = entries.iter().position(|(p, _)| p == point) {
+                let entry = entries.remove(position).unwrap();
+                let evals = entry.1.clone();
+                entries.push_front(entry);
+                return evals;
+            }
+        }
+
+        // the lock isn't held during the O(n) pass, concurrent misses on the same point
+        // both compute it and only one entry is kept
+        let evals = self.inner.batch_evaluate_lagrange_basis(point);
+
+        let mut entries = self.entries.lock().unwrap();
+        if !entries.iter().any(|(p, _)| p == point) {
+            if entries.len() == self.capacity {
+                entries.pop_back();
+            }
+            entries.push_front((*point, evals.clone()));
+        }
+        evals
+    }
+}
+
+#[cfg(test)]
+mod cache_tests {
+    use ark_bn254::Fr;
+    use ark_ff::UniformRand;
+    use ark_std::test_rng;
+
+    use crate::{cache::LagrangeCache, subtree::Pow2ProductSubtree, PolyProcessor};
+
+    #[test]
+    fn test_lagrange_cache() {
+        let n: usize = 32;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+        let cache = LagrangeCache::new(Box::new(subtree), 2);
+
+        let (a, b, c) = (Fr::rand(&mut rng), Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let a_evals = cache.batch_evaluate_lagrange_basis(&a);
+        cache.batch_evaluate_lagrange_basis(&b);
+        assert_eq!(cache.cached_points(), vec![b, a]);
+
+        // hit moves a to the front, so b gets evicted by c
+        assert_eq!(cache.batch_evaluate_lagrange_basis(&a), a_evals);
+        cache.batch_evaluate_lagrange_basis(&c);
+        assert_eq!(cache.cached_points(), vec![c, a]);
+
+        let subtree = cache.into_inner();
+        assert_eq!(subtree.batch_evaluate_lagrange_basis(&a), a_evals);
+    }
+}
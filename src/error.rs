@@ -2,4 +2,20 @@
 pub enum Error {
     EmptyRoots,
     NotPow2,
+    ExtensionTooSmall,
+    ZeroDenominator(Vec<usize>),
+    NonZeroRemainder,
+    NotVanishingOnSubset(Vec<usize>),
+    NoPadeApproximant,
+    CommonPoints(Vec<(usize, usize)>),
+    ModulusMismatch,
+    OverlappingCosets(Vec<(usize, usize)>),
+    InvalidPermutation,
+    DomainsNotDisjoint(Vec<usize>),
+    DegreeBoundExceeded(usize),
+    TaskMismatch,
+    VerificationFailed,
+    NoSubgroupOfSize(usize),
+    NoDisjointCoset,
+    SingleThreaded,
 }
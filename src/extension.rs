@@ -0,0 +1,126 @@
+//! Polynomials with coefficients in an extension E of the base prime field F over a domain
+//! of F points.
+//!
+//! f = sum_t f_t * e_t for the base coordinates f_t in `F[X]`, so every operation splits into
+//! one base field pass per coordinate: the tree nodes stay in F and only the dividend is lifted,
+//! which is roughly `extension_degree` times cheaper than running the tree over E.
+
+use ark_ff::{to_bytes, FftField, Field, FromBytes};
+use ark_poly::{univariate::DensePolynomial, UVPolynomial};
+
+use crate::PolyProcessor;
+
+/// Coordinates of an extension element over the base prime field,
+/// in the order expected by `Field::from_base_prime_field_elems`
+fn to_base_prime_field_elems<E: Field>(element: &E) -> Vec<E::BasePrimeField> {
+    // towers are written coordinate by coordinate, each one as a canonical prime field element
+    let bytes = to_bytes![element].unwrap();
+    let chunk_len = bytes.len() / E::extension_degree() as usize;
+    bytes
+        .chunks(chunk_len)
+        .map(|chunk| E::BasePrimeField::read(chunk).unwrap())
+        .collect()
+}
+
+/// Splits a vector over E into `extension_degree` vectors over F
+fn split<F: FftField, E: Field<BasePrimeField = F>>(elements: &[E]) -> Vec<Vec<F>> {
+    let d = E::extension_degree() as usize;
+    let mut coordinates = vec![Vec::with_capacity(elements.len()); d];
+    for element in elements {
+        for (coordinate, elem) in coordinates
+            .iter_mut()
+            .zip(to_base_prime_field_elems(element))
+        {
+            coordinate.push(elem);
+        }
+    }
+    coordinates
+}
+
+/// Inverse of `split`, shorter coordinates are padded with zeros
+fn combine<F: FftField, E: Field<BasePrimeField = F>>(coordinates: &[Vec<F>]) -> Vec<E> {
+    let len = coordinates.iter().map(|c| c.len()).max().unwrap_or(0);
+    (0..len)
+        .map(|i| {
+            let elems: Vec<F> = coordinates
+                .iter()
+                .map(|c| c.get(i).copied().unwrap_or_else(F::zero))
+                .collect();
+            E::from_base_prime_field_elems(&elems).unwrap()
+        })
+        .collect()
+}
+
+/// Evaluates f in `E[X]` over the domain of the processor, one base field descent per coordinate
+pub fn evaluate_over_domain<F: FftField, E: Field<BasePrimeField = F>>(
+    processor: &dyn PolyProcessor<F>,
+    f: &DensePolynomial<E>,
+) -> Vec<E> {
+    let evals: Vec<Vec<F>> = split(&f.coeffs)
+        .into_iter()
+        .map(|coeffs| {
+            processor.evaluate_over_domain(&DensePolynomial::from_coefficients_vec(coeffs))
+        })
+        .collect();
+    combine(&evals)
+}
+
+/// Interpolates evaluations in E over the domain of the processor,
+/// one base field interpolation per coordinate
+pub fn interpolate<F: FftField, E: Field<BasePrimeField = F>>(
+    processor: &dyn PolyProcessor<F>,
+    evals: &[E],
+) -> DensePolynomial<E> {
+    let coeffs: Vec<Vec<F>> = split(evals)
+        .iter()
+        .map(|evals| processor.interpolate(evals).coeffs)
+        .collect();
+    DensePolynomial::from_coefficients_vec(combine(&coeffs))
+}
+
+#[cfg(test)]
+mod extension_tests {
+    use ark_bn254::Fr;
+    use ark_ff::{field_new, Fp2, Fp2Parameters, UniformRand};
+    use ark_poly::{univariate::DensePolynomial, Polynomial, UVPolynomial};
+    use ark_std::test_rng;
+
+    use crate::{
+        extension::{evaluate_over_domain, interpolate, to_base_prime_field_elems},
+        subtree::Pow2ProductSubtree,
+    };
+
+    /// Fr[u]/(u^2 - 5), 5 generates Fr^* so it is not a square
+    type Fr2 = Fp2<Fr2Parameters>;
+
+    struct Fr2Parameters;
+
+    impl Fp2Parameters for Fr2Parameters {
+        type Fp = Fr;
+
+        const NONRESIDUE: Fr = field_new!(Fr, "5");
+
+        const QUADRATIC_NONRESIDUE: (Fr, Fr) = (field_new!(Fr, "0"), field_new!(Fr, "1"));
+
+        const FROBENIUS_COEFF_FP2_C1: &'static [Fr] = &[field_new!(Fr, "1"), field_new!(Fr, "-1")];
+    }
+
+    #[test]
+    fn test_extension_coefficients() {
+        let n: usize = 32;
+        let mut rng = test_rng();
+
+        let x = Fr2::rand(&mut rng);
+        assert_eq!(to_base_prime_field_elems(&x), vec![x.c0, x.c1]);
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+
+        let f = DensePolynomial::<Fr2>::rand(n - 1, &mut rng);
+        let evals = evaluate_over_domain(&subtree, &f);
+        for (eval, root) in evals.iter().zip(roots.iter()) {
+            assert_eq!(*eval, f.evaluate(&Fr2::new(*root, Fr::from(0u64))));
+        }
+        assert_eq!(interpolate(&subtree, &evals), f);
+    }
+}
@@ -3,6 +3,8 @@ use std::{marker::PhantomData, vec};
 use ark_ff::{FftField, Zero};
 use ark_poly::{univariate::DensePolynomial, Polynomial, UVPolynomial};
 
+use crate::parallel::join;
+
 pub struct FastEval<F: FftField> {
     _f: PhantomData<F>,
 }
@@ -17,20 +19,24 @@ impl<F: FftField> FastEval<F> {
         assert!(f.degree() < n);
 
         if n == 1 {
-            return vec![f.coeffs[0]];
+            // zero polynomial has no coefficients
+            return vec![f.coeffs.first().copied().unwrap_or_else(F::zero)];
         }
 
         //let f_ds = DenseOrSparsePolynomial::from(f);
         let lhs_divisor = layers[root.0 - 1][2 * root.1].clone();
         let rhs_divisor = layers[root.0 - 1][2 * root.1 + 1].clone();
 
-        let (_, r0) = Self::fast_divide_with_q_and_r(f, &lhs_divisor).unwrap();
-        let (_, r1) = Self::fast_divide_with_q_and_r(f, &rhs_divisor).unwrap();
-
-        let mut lhs_evals =
-            Self::divide_down_the_tree(layers, n / 2, (root.0 - 1, 2 * root.1), &r0);
-        let rhs_evals =
-            Self::divide_down_the_tree(layers, n / 2, (root.0 - 1, 2 * root.1 + 1), &r1);
+        let (mut lhs_evals, rhs_evals) = join(
+            || {
+                let (_, r0) = Self::fast_divide_with_q_and_r(f, &lhs_divisor).unwrap();
+                Self::divide_down_the_tree(layers, n / 2, (root.0 - 1, 2 * root.1), &r0)
+            },
+            || {
+                let (_, r1) = Self::fast_divide_with_q_and_r(f, &rhs_divisor).unwrap();
+                Self::divide_down_the_tree(layers, n / 2, (root.0 - 1, 2 * root.1 + 1), &r1)
+            },
+        );
 
         lhs_evals.extend_from_slice(&rhs_evals);
         lhs_evals
@@ -50,9 +56,10 @@ impl<F: FftField> FastEval<F> {
         let lhs_bounds = (index_bounds.0, index_bounds.0 + len);
         let rhs_bounds = (lhs_bounds.1 + 1, index_bounds.1);
 
-        let r0 = Self::multiply_up_the_tree(layers, lhs_bounds, (root.0 - 1, 2 * root.1), evals);
-        let r1 =
-            Self::multiply_up_the_tree(layers, rhs_bounds, (root.0 - 1, 2 * root.1 + 1), evals);
+        let (r0, r1) = join(
+            || Self::multiply_up_the_tree(layers, lhs_bounds, (root.0 - 1, 2 * root.1), evals),
+            || Self::multiply_up_the_tree(layers, rhs_bounds, (root.0 - 1, 2 * root.1 + 1), evals),
+        );
 
         let lhs = &layers[root.0 - 1][2 * root.1];
         let rhs = &layers[root.0 - 1][2 * root.1 + 1];
@@ -117,13 +124,76 @@ impl<F: FftField> FastEval<F> {
             let rev_g = Self::poly_reverse(divisor); //reverse of g
             let inv_rev_g = Self::poly_inverse(&rev_g, poly.degree() as u32 + 1).unwrap();
             let tmp = &rev_f * &inv_rev_g;
-            let rev_q = Self::poly_trim(&tmp, poly.degree() - divisor.degree() + 1);
-            let quotient = Self::poly_reverse(&rev_q);
+            // rev(q) is reversed w.r.t. deg(f)-deg(g) and not its own degree, since
+            // trailing zeros of q become leading zeros of rev(q)
+            let q_len = poly.degree() - divisor.degree() + 1;
+            let mut rev_q = Self::poly_trim(&tmp, q_len).coeffs;
+            rev_q.resize(q_len, F::zero());
+            rev_q.reverse();
+            let quotient = DensePolynomial::from_coefficients_vec(rev_q);
             let remainder = poly + &(&(&quotient * divisor) * (-F::one()));
 
             Some((quotient, remainder))
         }
     }
+
+    /// Computes prod(X - root_i) by splitting roots in halves, works for any number of roots
+    pub fn product_of_monomials(roots: &[F]) -> DensePolynomial<F> {
+        match roots.len() {
+            0 => DensePolynomial::from_coefficients_slice(&[F::one()]),
+            1 => DensePolynomial::from_coefficients_slice(&[-roots[0], F::one()]),
+            len => {
+                let lhs = Self::product_of_monomials(&roots[..len / 2]);
+                let rhs = Self::product_of_monomials(&roots[len / 2..]);
+                &lhs * &rhs
+            }
+        }
+    }
+
+    /// Outputs poly mod X^n - c by folding the coefficients of X^(q*n + i) onto c^q * X^i,
+    /// which is O(deg poly) instead of a division
+    pub fn reduce_mod_xn_minus_c(poly: &DensePolynomial<F>, n: usize, c: F) -> DensePolynomial<F> {
+        assert!(n > 0);
+        let mut chunks = poly.coeffs.chunks(n);
+        let mut r = chunks
+            .next()
+            .map(|chunk| chunk.to_vec())
+            .unwrap_or_default();
+
+        let mut c_pow = F::one();
+        for chunk in chunks {
+            c_pow *= c;
+            for (r_i, coeff) in r.iter_mut().zip(chunk.iter()) {
+                *r_i += c_pow * coeff;
+            }
+        }
+        DensePolynomial::from_coefficients_vec(r)
+    }
+
+    /// Quotient of poly by X - root with synthetic division, the remainder poly(root) is dropped
+    pub fn divide_by_monomial(poly: &DensePolynomial<F>, root: &F) -> DensePolynomial<F> {
+        if poly.coeffs.len() <= 1 {
+            return DensePolynomial::zero();
+        }
+
+        let mut quotient = vec![F::zero(); poly.coeffs.len() - 1];
+        let mut carry = F::zero();
+        for (q_i, coeff) in quotient.iter_mut().zip(poly.coeffs.iter().skip(1)).rev() {
+            carry = carry * root + coeff;
+            *q_i = carry;
+        }
+        DensePolynomial::from_coefficients_vec(quotient)
+    }
+
+    pub fn derivative(poly: &DensePolynomial<F>) -> DensePolynomial<F> {
+        let mut coeffs = Vec::with_capacity(poly.coeffs.len().saturating_sub(1));
+        let mut i = F::one();
+        for coeff in poly.coeffs.iter().skip(1) {
+            coeffs.push(*coeff * i);
+            i += F::one();
+        }
+        DensePolynomial::from_coefficients_vec(coeffs)
+    }
 }
 
 //////////////////////////////////////////////////////
@@ -138,7 +208,7 @@ pub mod tests {
     };
 
     use ark_bn254::Fr;
-    use ark_ff::One;
+    use ark_ff::{One, UniformRand, Zero};
     use std::time::Instant;
 
     #[allow(non_snake_case)]
@@ -188,6 +258,58 @@ pub mod tests {
         }
     }
 
+    #[allow(non_snake_case)]
+    #[test]
+    pub fn test_fast_poly_division_by_monomial_quotient() {
+        let rng = &mut ark_std::test_rng();
+
+        // quotient X^3 has trailing zeros
+        let g_poly = DensePolynomial::<Fr>::rand(5, rng);
+        let x_cube = DensePolynomial::from_coefficients_vec(vec![
+            Fr::zero(),
+            Fr::zero(),
+            Fr::zero(),
+            Fr::one(),
+        ]);
+        let r_poly = DensePolynomial::<Fr>::rand(3, rng);
+        let c_poly = &(&x_cube * &g_poly) + &r_poly;
+
+        let (q, r) = FastEval::fast_divide_with_q_and_r(&c_poly, &g_poly).unwrap();
+        assert_eq!(q, x_cube);
+        assert_eq!(r, r_poly);
+    }
+
+    #[test]
+    pub fn test_divide_by_monomial() {
+        let rng = &mut ark_std::test_rng();
+
+        for deg in [0, 1, 5, 16] {
+            let poly = DensePolynomial::<Fr>::rand(deg, rng);
+            let root = Fr::rand(rng);
+            let monomial = DensePolynomial::from_coefficients_slice(&[-root, Fr::one()]);
+
+            let (q, _) = FastEval::fast_divide_with_q_and_r(&poly, &monomial).unwrap();
+            assert_eq!(FastEval::divide_by_monomial(&poly, &root), q);
+        }
+    }
+
+    #[test]
+    pub fn test_reduce_mod_xn_minus_c() {
+        let rng = &mut ark_std::test_rng();
+
+        for (deg, n) in [(0, 4), (3, 4), (4, 4), (37, 8), (64, 1)] {
+            let poly = DensePolynomial::<Fr>::rand(deg, rng);
+            let c = Fr::rand(rng);
+            let mut xn_minus_c = vec![Fr::zero(); n + 1];
+            xn_minus_c[0] = -c;
+            xn_minus_c[n] = Fr::one();
+            let xn_minus_c = DensePolynomial::from_coefficients_vec(xn_minus_c);
+
+            let (_, r) = FastEval::fast_divide_with_q_and_r(&poly, &xn_minus_c).unwrap();
+            assert_eq!(FastEval::reduce_mod_xn_minus_c(&poly, n, c), r);
+        }
+    }
+
     #[allow(non_snake_case)]
     #[test]
     pub fn compare_fast_poly_division() {
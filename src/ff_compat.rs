@@ -0,0 +1,224 @@
+//! Compatibility layer for fields implementing `ff::PrimeField` (zcash, halo2curves, ...).
+//!
+//! This is a conversion layer, not an arkworks `Field` implementation for `ff` types: elements
+//! are mapped onto an arkworks prime field with the same modulus through their canonical
+//! little-endian byte representation and all the algorithms run unchanged over that field.
+//! So an `ff` field is only supported if an arkworks field with its modulus exists (the BN254
+//! and BLS12-381 scalar fields, Pasta, ...), there is no generic wrapper for arbitrary moduli.
+//!
+//! `FfProcessor` converts the most common calls, every other `PolyProcessor` operation is
+//! reachable through [`FfProcessor::inner`] and the slice conversions, which check that the
+//! two fields are compatible.
+
+use std::marker::PhantomData;
+
+use ark_ff::{BigInteger, PrimeField};
+use ark_poly::UVPolynomial;
+use ff::PrimeField as FfPrimeField;
+
+use crate::{error::Error, PolyProcessor, PolyProcessorStrategy};
+
+/// Converts an `ff` field element to the arkworks field with the same modulus, unchecked: a
+/// mismatched modulus silently reduces, so callers run `check_compatible` first
+pub(crate) fn to_ark<G: FfPrimeField, F: PrimeField>(element: &G) -> F {
+    F::from_le_bytes_mod_order(element.to_repr().as_ref())
+}
+
+/// Converts an arkworks field element to the `ff` field with the same modulus, unchecked: it
+/// panics if the element is not below the modulus of G, so callers run `check_compatible` first
+pub(crate) fn from_ark<F: PrimeField, G: FfPrimeField>(element: &F) -> G {
+    let bytes = element.into_repr().to_bytes_le();
+    let mut repr = G::Repr::default();
+    let len = repr.as_ref().len().min(bytes.len());
+    repr.as_mut()[..len].copy_from_slice(&bytes[..len]);
+    // canonical representation of an element below the shared modulus
+    Option::from(G::from_repr(repr)).unwrap()
+}
+
+/// Checks that G and F share the modulus and the byte representation of G is little-endian,
+/// -1 is mapped to -1 only if both hold
+pub fn check_compatible<G: FfPrimeField, F: PrimeField>() -> Result<(), Error> {
+    if to_ark::<G, F>(&-G::ONE) == -F::one() {
+        Ok(())
+    } else {
+        Err(Error::ModulusMismatch)
+    }
+}
+
+/// Processor over an `ff` field G, backed by a `PolyProcessor` over the arkworks field F.
+/// Polynomials are passed as coefficient vectors in increasing order of degree
+pub struct FfProcessor<G: FfPrimeField, F: PrimeField> {
+    inner: Box<dyn PolyProcessor<F>>,
+    _g: PhantomData<G>,
+}
+
+impl<G: FfPrimeField, F: PrimeField> FfProcessor<G, F> {
+    pub fn resolve(roots: &[G]) -> Result<Self, Error> {
+        check_compatible::<G, F>()?;
+
+        let roots: Vec<F> = roots.iter().map(to_ark).collect();
+        let inner = PolyProcessorStrategy::resolve(&roots)?;
+        Ok(Self {
+            inner,
+            _g: PhantomData,
+        })
+    }
+
+    /// The arkworks processor, for the operations without a converting wrapper here
+    pub fn inner(&self) -> &dyn PolyProcessor<F> {
+        self.inner.as_ref()
+    }
+
+    pub fn get_vanishing(&self) -> Vec<G> {
+        self.inner
+            .get_vanishing()
+            .coeffs
+            .iter()
+            .map(from_ark)
+            .collect()
+    }
+
+    pub fn evaluate_vanishing_at(&self, point: &G) -> G {
+        from_ark(&self.inner.evaluate_vanishing_at(&to_ark(point)))
+    }
+
+    pub fn get_ri(&self) -> Vec<G> {
+        self.inner.get_ri().iter().map(from_ark).collect()
+    }
+
+    pub fn evaluate_over_domain(&self, f: &[G]) -> Vec<G> {
+        let f = UVPolynomial::from_coefficients_vec(f.iter().map(to_ark).collect());
+        let evals = self.inner.evaluate_over_domain(&f);
+        evals.iter().map(from_ark).collect()
+    }
+
+    pub fn interpolate(&self, evals: &[G]) -> Vec<G> {
+        let evals: Vec<F> = evals.iter().map(to_ark).collect();
+        self.inner
+            .interpolate(&evals)
+            .coeffs
+            .iter()
+            .map(from_ark)
+            .collect()
+    }
+
+    pub fn batch_evaluate_lagrange_basis(&self, point: &G) -> Vec<G> {
+        let li_evals = self.inner.batch_evaluate_lagrange_basis(&to_ark(point));
+        li_evals.iter().map(from_ark).collect()
+    }
+}
+
+/// Converts `ff` field elements to the arkworks field with the same modulus,
+/// fails with `ModulusMismatch` if the fields are not compatible
+pub fn to_ark_slice<G: FfPrimeField, F: PrimeField>(elements: &[G]) -> Result<Vec<F>, Error> {
+    check_compatible::<G, F>()?;
+    Ok(elements.iter().map(to_ark).collect())
+}
+
+/// Converts arkworks field elements to the `ff` field with the same modulus,
+/// fails with `ModulusMismatch` if the fields are not compatible
+pub fn from_ark_slice<F: PrimeField, G: FfPrimeField>(elements: &[F]) -> Result<Vec<G>, Error> {
+    check_compatible::<G, F>()?;
+    Ok(elements.iter().map(from_ark).collect())
+}
+
+#[cfg(test)]
+mod ff_compat_tests {
+    use ark_bn254::Fr;
+    use ark_ff::{Field as ArkField, One, UniformRand};
+    use ark_poly::{univariate::DensePolynomial, Polynomial, UVPolynomial};
+    use ark_std::test_rng;
+    use ff::Field;
+
+    use self::{bls12_381::Bls12381Fr, bn254::Bn254Fr};
+    use crate::{
+        error::Error,
+        ff_compat::{
+            check_compatible, from_ark, from_ark_slice, to_ark, to_ark_slice, FfProcessor,
+        },
+    };
+
+    mod bn254 {
+        use ff::PrimeField;
+
+        #[derive(PrimeField)]
+        #[PrimeFieldModulus = "21888242871839275222246405745257275088548364400416034343698204186575808495617"]
+        #[PrimeFieldGenerator = "5"]
+        #[PrimeFieldReprEndianness = "little"]
+        pub struct Bn254Fr([u64; 4]);
+    }
+
+    mod bls12_381 {
+        use ff::PrimeField;
+
+        #[derive(PrimeField)]
+        #[PrimeFieldModulus = "52435875175126190479447740508185965837690552500527637822603658699938581184513"]
+        #[PrimeFieldGenerator = "7"]
+        #[PrimeFieldReprEndianness = "little"]
+        pub struct Bls12381Fr([u64; 4]);
+    }
+
+    #[test]
+    fn test_conversions() {
+        let mut rng = test_rng();
+
+        assert!(check_compatible::<Bn254Fr, Fr>().is_ok());
+        assert_eq!(
+            check_compatible::<Bls12381Fr, Fr>(),
+            Err(Error::ModulusMismatch)
+        );
+
+        let a = Fr::rand(&mut rng);
+        let b = Fr::rand(&mut rng);
+        let (a_ff, b_ff): (Bn254Fr, Bn254Fr) = (from_ark(&a), from_ark(&b));
+        assert_eq!(to_ark::<_, Fr>(&a_ff), a);
+        assert_eq!(to_ark::<_, Fr>(&(a_ff * b_ff)), a * b);
+        assert_eq!(
+            to_ark::<_, Fr>(&a_ff.invert().unwrap()),
+            a.inverse().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_ff_processor() {
+        let n: usize = 32;
+        let mut rng = test_rng();
+
+        let roots: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let roots_ff: Vec<Bn254Fr> = roots.iter().map(from_ark).collect();
+        let processor = FfProcessor::<Bn254Fr, Fr>::resolve(&roots_ff).unwrap();
+
+        let f = DensePolynomial::<Fr>::rand(n - 1, &mut rng);
+        let f_ff: Vec<Bn254Fr> = f.coeffs.iter().map(from_ark).collect();
+
+        let evals = processor.evaluate_over_domain(&f_ff);
+        for (eval, root) in evals.iter().zip(roots.iter()) {
+            assert_eq!(to_ark::<_, Fr>(eval), f.evaluate(root));
+        }
+        assert_eq!(processor.interpolate(&evals), f_ff);
+
+        // operations without a wrapper go through the arkworks processor
+        let weights_ff: Vec<Bn254Fr> = (0..n).map(|_| Bn254Fr::random(&mut rng)).collect();
+        let sum: Bn254Fr = from_ark(
+            &processor
+                .inner()
+                .weighted_sum(&f, &to_ark_slice::<_, Fr>(&weights_ff).unwrap()),
+        );
+        let expected: Bn254Fr = evals
+            .iter()
+            .zip(weights_ff.iter())
+            .map(|(e, w)| *e * w)
+            .sum();
+        assert_eq!(sum, expected);
+
+        assert!(FfProcessor::<Bls12381Fr, Fr>::resolve(&[]).is_err());
+        assert_eq!(
+            to_ark_slice::<_, Fr>(&[Bls12381Fr::ONE]),
+            Err(Error::ModulusMismatch)
+        );
+        assert_eq!(
+            from_ark_slice::<_, Bls12381Fr>(&[Fr::one()]),
+            Err(Error::ModulusMismatch)
+        );
+    }
+}
@@ -1,10 +1,12 @@
+use std::collections::HashSet;
+
 use ark_ff::FftField;
 use ark_poly::{
     univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain, Polynomial,
     UVPolynomial,
 };
 
-use crate::{error::Error, PolyProcessor};
+use crate::{error::Error, fast_eval::FastEval, PolyProcessor};
 
 pub struct FftProcessor<F: FftField> {
     domain: GeneralEvaluationDomain<F>,
@@ -20,19 +22,40 @@ impl<F: FftField> FftProcessor<F> {
 }
 
 impl<F: FftField> PolyProcessor<F> for FftProcessor<F> {
+    fn size(&self) -> usize {
+        self.domain.size()
+    }
+
+    fn elements(&self) -> Vec<F> {
+        self.domain.elements().collect()
+    }
+
     fn get_vanishing(&self) -> DensePolynomial<F> {
         self.domain.vanishing_polynomial().into()
     }
 
+    fn evaluate_vanishing_at(&self, point: &F) -> F {
+        self.domain.evaluate_vanishing_polynomial(*point)
+    }
+
     fn get_ri(&self) -> Vec<F> {
+        // ri = 1/zH'(w^i) = w^i / n
+        let n_inv = self.domain.size_as_field_element().inverse().unwrap();
+        self.domain
+            .elements()
+            .map(|omega_i| omega_i * n_inv)
+            .collect()
+    }
+
+    fn get_vanishing_derivative_evals(&self) -> Vec<F> {
         // zH'(w^i) =  n * w^(-i)
         let n = self.domain.size();
         let n_field = self.domain.size_as_field_element();
-        let mut ri = Vec::with_capacity(n);
+        let mut evals = Vec::with_capacity(n);
         for i in 0..n {
-            ri.push(n_field * self.domain.element(n - i));
+            evals.push(n_field * self.domain.element(n - i));
         }
-        ri
+        evals
     }
 
     fn evaluate_over_domain(&self, f: &DensePolynomial<F>) -> Vec<F> {
@@ -40,12 +63,358 @@ impl<F: FftField> PolyProcessor<F> for FftProcessor<F> {
         self.domain.fft(f)
     }
 
+    fn evaluate_derivatives_over_domain(&self, f: &DensePolynomial<F>, k: usize) -> Vec<Vec<F>> {
+        assert!(f.degree() < self.domain.size());
+        let mut derivatives_evals = Vec::with_capacity(k + 1);
+        let mut derivative = f.clone();
+        for _ in 0..=k {
+            derivatives_evals.push(self.domain.fft(&derivative));
+            derivative = FastEval::derivative(&derivative);
+        }
+        derivatives_evals
+    }
+
     fn interpolate(&self, evals: &[F]) -> DensePolynomial<F> {
         assert_eq!(evals.len(), self.domain.size());
         DensePolynomial::from_coefficients_slice(&self.domain.ifft(evals))
     }
 
+    fn reduce_mod_vanishing(&self, f: &DensePolynomial<F>) -> DensePolynomial<F> {
+        // zH = X^n - 1
+        FastEval::reduce_mod_xn_minus_c(f, self.domain.size(), F::one())
+    }
+
     fn batch_evaluate_lagrange_basis(&self, point: &F) -> Vec<F> {
         self.domain.evaluate_all_lagrange_coefficients(*point)
     }
 }
+
+/// Shifted subgroup `g * <w>`, evaluated with FFTs of f(g * X)
+pub struct CosetFftProcessor<F: FftField> {
+    domain: GeneralEvaluationDomain<F>,
+    offset: F,
+}
+
+impl<F: FftField> CosetFftProcessor<F> {
+    pub fn construct(domain: GeneralEvaluationDomain<F>, offset: F) -> Result<Self, Error> {
+        if domain.size() & (domain.size() - 1) != 0 {
+            return Err(Error::NotPow2);
+        }
+        assert!(!offset.is_zero());
+        Ok(Self { domain, offset })
+    }
+
+    pub fn offset(&self) -> F {
+        self.offset
+    }
+
+    /// x is in `g * <w>` iff x^m = g^m, fails with the indices of the elements in the coset
+    pub fn check_disjoint(&self, elements: &[F]) -> Result<(), Error> {
+        let m = self.domain.size() as u64;
+        let offset_pow = self.offset.pow([m]);
+        let common: Vec<_> = elements
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| x.pow([m]) == offset_pow)
+            .map(|(i, _)| i)
+            .collect();
+        if common.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::DomainsNotDisjoint(common))
+        }
+    }
+}
+
+/// For the coefficients of f(X) outputs the coefficients of f(offset * X)
+fn scale_coefficients<F: FftField>(coeffs: &[F], offset: F) -> Vec<F> {
+    let mut offset_pow = F::one();
+    coeffs
+        .iter()
+        .map(|coeff| {
+            let scaled = *coeff * offset_pow;
+            offset_pow *= offset;
+            scaled
+        })
+        .collect()
+}
+
+/// Outputs a coset of the subgroup of the given size which is verified to be disjoint from
+/// the domain of `h`. The offsets g, g^2, ..., g^((p - 1) / m) for the multiplicative generator g
+/// lie in all the distinct cosets, so at most min(|H| + 1, (p - 1) / m) of them are tried.
+/// Fails with `NoDisjointCoset` if H hits every coset
+pub fn disjoint_coset<F: FftField>(
+    h: &dyn PolyProcessor<F>,
+    size: usize,
+) -> Result<CosetFftProcessor<F>, Error> {
+    let domain = GeneralEvaluationDomain::<F>::new(size).ok_or(Error::NoSubgroupOfSize(size))?;
+    if domain.size() != size {
+        return Err(Error::NotPow2);
+    }
+
+    // x is in g * <w> iff x^m = g^m
+    let m = size as u64;
+    let hit: HashSet<F> = h.elements().iter().map(|x| x.pow([m])).collect();
+
+    let generator = F::multiplicative_generator();
+    let first = generator.pow([m]);
+    let mut offset = generator;
+    while hit.contains(&offset.pow([m])) {
+        offset *= generator;
+        // g^((p - 1) / m + 1) is back in the coset of g
+        if offset.pow([m]) == first {
+            return Err(Error::NoDisjointCoset);
+        }
+    }
+
+    let coset = CosetFftProcessor::construct(domain, offset)?;
+    // also guards against hash collisions of the lookup above
+    coset.check_disjoint(&h.elements())?;
+    Ok(coset)
+}
+
+impl<F: FftField> PolyProcessor<F> for CosetFftProcessor<F> {
+    fn size(&self) -> usize {
+        self.domain.size()
+    }
+
+    fn elements(&self) -> Vec<F> {
+        self.domain
+            .elements()
+            .map(|omega_i| self.offset * omega_i)
+            .collect()
+    }
+
+    fn get_vanishing(&self) -> DensePolynomial<F> {
+        // X^m - g^m
+        let m = self.domain.size();
+        let mut coeffs = vec![F::zero(); m + 1];
+        coeffs[0] = -self.offset.pow([m as u64]);
+        coeffs[m] = F::one();
+        DensePolynomial::from_coefficients_vec(coeffs)
+    }
+
+    fn evaluate_vanishing_at(&self, point: &F) -> F {
+        let m = [self.domain.size() as u64];
+        point.pow(m) - self.offset.pow(m)
+    }
+
+    fn get_ri(&self) -> Vec<F> {
+        // ri = 1/zH'(x_i) = x_i / (m * g^m) = w^i * g^(1-m) / m
+        let m = self.domain.size() as u64;
+        let factor = (self.domain.size_as_field_element() * self.offset.pow([m - 1]))
+            .inverse()
+            .unwrap();
+        self.domain
+            .elements()
+            .map(|omega_i| omega_i * factor)
+            .collect()
+    }
+
+    fn get_vanishing_derivative_evals(&self) -> Vec<F> {
+        // zH'(x_i) = m * x_i^(m-1) = m * g^(m-1) * w^(-i)
+        let n = self.domain.size();
+        let factor = self.domain.size_as_field_element() * self.offset.pow([n as u64 - 1]);
+        let mut evals = Vec::with_capacity(n);
+        for i in 0..n {
+            evals.push(factor * self.domain.element(n - i));
+        }
+        evals
+    }
+
+    fn evaluate_over_domain(&self, f: &DensePolynomial<F>) -> Vec<F> {
+        assert!(f.degree() < self.domain.size());
+        self.domain.fft(&scale_coefficients(&f.coeffs, self.offset))
+    }
+
+    fn evaluate_derivatives_over_domain(&self, f: &DensePolynomial<F>, k: usize) -> Vec<Vec<F>> {
+        assert!(f.degree() < self.domain.size());
+        let mut derivatives_evals = Vec::with_capacity(k + 1);
+        let mut derivative = f.clone();
+        for _ in 0..=k {
+            derivatives_evals.push(self.evaluate_over_domain(&derivative));
+            derivative = FastEval::derivative(&derivative);
+        }
+        derivatives_evals
+    }
+
+    fn interpolate(&self, evals: &[F]) -> DensePolynomial<F> {
+        assert_eq!(evals.len(), self.domain.size());
+        let coeffs = self.domain.ifft(evals);
+        let offset_inv = self.offset.inverse().unwrap();
+        DensePolynomial::from_coefficients_vec(scale_coefficients(&coeffs, offset_inv))
+    }
+
+    fn reduce_mod_vanishing(&self, f: &DensePolynomial<F>) -> DensePolynomial<F> {
+        // zH = X^m - g^m
+        let m = self.domain.size();
+        FastEval::reduce_mod_xn_minus_c(f, m, self.offset.pow([m as u64]))
+    }
+
+    fn batch_evaluate_lagrange_basis(&self, point: &F) -> Vec<F> {
+        // Li of the coset at X is Li of the subgroup at X/g
+        let offset_inv = self.offset.inverse().unwrap();
+        self.domain
+            .evaluate_all_lagrange_coefficients(*point * offset_inv)
+    }
+}
+
+#[cfg(test)]
+mod fft_tests {
+    use ark_bn254::Fr;
+    use ark_ff::{FftField, Field, One, UniformRand, Zero};
+    use ark_poly::{
+        univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain, Polynomial,
+        UVPolynomial,
+    };
+    use ark_std::test_rng;
+
+    use crate::{
+        error::Error,
+        fast_eval::FastEval,
+        fft::{disjoint_coset, CosetFftProcessor, FftProcessor},
+        subtree::Pow2ProductSubtree,
+        test_fields::F97,
+        PolyProcessor,
+    };
+
+    #[test]
+    fn test_ri() {
+        let n: usize = 32;
+        let domain = GeneralEvaluationDomain::<Fr>::new(n).unwrap();
+        let fft_processor = FftProcessor::construct(domain).unwrap();
+
+        let ri = fft_processor.get_ri();
+        let vanishing_derivative_evals = fft_processor.get_vanishing_derivative_evals();
+        for (ri, zh_prime_i) in ri.iter().zip(vanishing_derivative_evals.iter()) {
+            assert_eq!(*ri * zh_prime_i, Fr::one());
+        }
+
+        // Li(X) = ri * zH(X) / (X - x_i) evaluated at x_i
+        let alpha = domain.element(1) + domain.element(3);
+        let zh_alpha = fft_processor.evaluate_vanishing_at(&alpha);
+        assert_eq!(zh_alpha, fft_processor.get_vanishing().evaluate(&alpha));
+        let li_evals = fft_processor.batch_evaluate_lagrange_basis(&alpha);
+        for (i, li_eval) in li_evals.iter().enumerate() {
+            let expected = ri[i] * zh_alpha / (alpha - domain.element(i));
+            assert_eq!(*li_eval, expected);
+        }
+    }
+
+    #[test]
+    fn test_disjoint_coset() {
+        let n: usize = 16;
+        let m: usize = 64;
+        let mut rng = test_rng();
+
+        // H hits the coset of the first candidate offset
+        let domain = GeneralEvaluationDomain::<Fr>::new(m).unwrap();
+        let generator = Fr::multiplicative_generator();
+        let mut roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        roots[6] = generator * domain.element(3);
+        let h = Pow2ProductSubtree::construct(&roots).unwrap();
+
+        let first = CosetFftProcessor::construct(domain, generator).unwrap();
+        assert_eq!(
+            first.check_disjoint(&roots).err(),
+            Some(Error::DomainsNotDisjoint(vec![6]))
+        );
+
+        let coset = disjoint_coset(&h, m).unwrap();
+        assert_ne!(coset.offset(), generator);
+        assert!(coset.check_disjoint(&roots).is_ok());
+        assert_eq!(disjoint_coset(&h, 48).err(), Some(Error::NotPow2));
+
+        let elements = coset.elements();
+        let vanishing = coset.get_vanishing();
+        for x in &elements {
+            assert!(vanishing.evaluate(x).is_zero());
+            assert!(!h.evaluate_vanishing_at(x).is_zero());
+        }
+
+        let f = DensePolynomial::<Fr>::rand(m - 1, &mut rng);
+        let f_evals = coset.evaluate_over_domain(&f);
+        for (eval, x) in f_evals.iter().zip(elements.iter()) {
+            assert_eq!(*eval, f.evaluate(x));
+        }
+        assert_eq!(coset.interpolate(&f_evals), f);
+
+        let alpha = Fr::rand(&mut rng);
+        let li_evals = coset.batch_evaluate_lagrange_basis(&alpha);
+        let f_alpha = li_evals
+            .iter()
+            .zip(f_evals.iter())
+            .fold(Fr::zero(), |acc, (li, fi)| acc + *li * fi);
+        assert_eq!(f_alpha, f.evaluate(&alpha));
+        assert_eq!(
+            coset.evaluate_vanishing_at(&alpha),
+            vanishing.evaluate(&alpha)
+        );
+
+        let derivative_evals = coset.evaluate_derivatives_over_domain(&f, 1);
+        for ((ri, zh_prime_i), x) in coset
+            .get_ri()
+            .iter()
+            .zip(coset.get_vanishing_derivative_evals().iter())
+            .zip(elements.iter())
+        {
+            assert_eq!(*ri * zh_prime_i, Fr::one());
+            assert_eq!(*zh_prime_i, Fr::from(m as u64) * x.pow([m as u64 - 1]));
+        }
+        assert_eq!(derivative_evals[0], f_evals);
+    }
+
+    #[test]
+    fn test_disjoint_coset_exhausted() {
+        // the subgroup of size 32 has (97 - 1) / 32 = 3 cosets, represented by 1, 5 and 25
+        let m: usize = 32;
+        let generator = F97::multiplicative_generator();
+        assert_eq!(generator, F97::from(5u64));
+
+        let h = Pow2ProductSubtree::construct(&[F97::from(1u64), F97::from(5u64)]).unwrap();
+        let coset = disjoint_coset(&h, m).unwrap();
+        assert_eq!(coset.offset(), F97::from(25u64));
+
+        let roots = [1u64, 5, 25, 2].map(F97::from);
+        let h = Pow2ProductSubtree::construct(&roots).unwrap();
+        assert_eq!(disjoint_coset(&h, m).err(), Some(Error::NoDisjointCoset));
+        assert_eq!(
+            disjoint_coset(&h, 64).err(),
+            Some(Error::NoSubgroupOfSize(64))
+        );
+    }
+
+    #[test]
+    fn test_reduce_mod_vanishing() {
+        let n: usize = 32;
+        let mut rng = test_rng();
+
+        let domain = GeneralEvaluationDomain::<Fr>::new(n).unwrap();
+        let fft_processor = FftProcessor::construct(domain).unwrap();
+        let coset = CosetFftProcessor::construct(domain, Fr::rand(&mut rng)).unwrap();
+
+        let f = DensePolynomial::<Fr>::rand(3 * n + 5, &mut rng);
+        for processor in [&fft_processor as &dyn PolyProcessor<Fr>, &coset] {
+            let (_, r) =
+                FastEval::fast_divide_with_q_and_r(&f, &processor.get_vanishing()).unwrap();
+            assert_eq!(processor.reduce_mod_vanishing(&f), r);
+        }
+    }
+
+    #[test]
+    fn test_multiply_evals_without_subgroup() {
+        // the subgroup of size 32 is the largest one in F97
+        let n: usize = 32;
+        let domain = GeneralEvaluationDomain::<F97>::new(n).unwrap();
+        let fft_processor = FftProcessor::construct(domain).unwrap();
+
+        let evals: Vec<_> = (0..n as u64).map(F97::from).collect();
+        let f = fft_processor.interpolate(&evals);
+        assert_eq!(fft_processor.evaluate_over_domain(&f), evals);
+
+        assert_eq!(
+            fft_processor.multiply_evals(&evals, &evals, None).err(),
+            Some(Error::NoSubgroupOfSize(2 * n))
+        );
+    }
+}
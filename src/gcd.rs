@@ -0,0 +1,295 @@
+use ark_ff::{FftField, Zero};
+use ark_poly::{univariate::DensePolynomial, UVPolynomial};
+
+use crate::{error::Error, fast_eval::FastEval};
+
+/// Outcome of checking that a vanishing polynomial zH has distinct roots
+#[derive(Debug, PartialEq)]
+pub enum RootsCertificate<F: FftField> {
+    /// u, v with u * zH + v * zH' = 1, which proves gcd(zH, zH') = 1
+    Distinct {
+        u: DensePolynomial<F>,
+        v: DensePolynomial<F>,
+    },
+    /// monic gcd(zH, zH'), its roots are exactly the repeated roots of zH
+    Repeated(DensePolynomial<F>),
+}
+
+impl<F: FftField> RootsCertificate<F> {
+    /// Checks the bezout identity of a `Distinct` certificate against zH
+    pub fn verify(&self, vanishing: &DensePolynomial<F>) -> bool {
+        match self {
+            Self::Distinct { u, v } => {
+                let vanishing_derivative = FastEval::derivative(vanishing);
+                let one = DensePolynomial::from_coefficients_slice(&[F::one()]);
+                &(u * vanishing) + &(v * &vanishing_derivative) == one
+            }
+            Self::Repeated(_) => false,
+        }
+    }
+}
+
+/// 2x2 matrix of polynomials describing a sequence of euclidean steps
+struct PolyMatrix<F: FftField>([[DensePolynomial<F>; 2]; 2]);
+
+impl<F: FftField> PolyMatrix<F> {
+    fn identity() -> Self {
+        let one = DensePolynomial::from_coefficients_slice(&[F::one()]);
+        Self([
+            [one.clone(), DensePolynomial::zero()],
+            [DensePolynomial::zero(), one],
+        ])
+    }
+
+    /// [[0, 1], [1, -q]] maps (a, b) to (b, a - q * b)
+    fn euclidean_step(q: &DensePolynomial<F>) -> Self {
+        let one = DensePolynomial::from_coefficients_slice(&[F::one()]);
+        Self([[DensePolynomial::zero(), one.clone()], [one, -q.clone()]])
+    }
+
+    fn apply(
+        &self,
+        a: &DensePolynomial<F>,
+        b: &DensePolynomial<F>,
+    ) -> (DensePolynomial<F>, DensePolynomial<F>) {
+        let m = &self.0;
+        (
+            &(&m[0][0] * a) + &(&m[0][1] * b),
+            &(&m[1][0] * a) + &(&m[1][1] * b),
+        )
+    }
+
+    fn mul(&self, rhs: &Self) -> Self {
+        let (a, b) = (&self.0, &rhs.0);
+        let entry = |i: usize, j: usize| &(&a[i][0] * &b[0][j]) + &(&a[i][1] * &b[1][j]);
+        Self([[entry(0, 0), entry(0, 1)], [entry(1, 0), entry(1, 1)]])
+    }
+}
+
+/// Number of coefficients, zero polynomial has none
+fn len<F: FftField>(poly: &DensePolynomial<F>) -> usize {
+    poly.coeffs.len()
+}
+
+/// for p(X) outputs p(X) div X^k
+fn shift_down<F: FftField>(poly: &DensePolynomial<F>, k: usize) -> DensePolynomial<F> {
+    if len(poly) <= k {
+        return DensePolynomial::zero();
+    }
+    DensePolynomial::from_coefficients_slice(&poly.coeffs[k..])
+}
+
+fn divide<F: FftField>(
+    a: &DensePolynomial<F>,
+    b: &DensePolynomial<F>,
+) -> (DensePolynomial<F>, DensePolynomial<F>) {
+    FastEval::fast_divide_with_q_and_r(a, b).unwrap()
+}
+
+/// For len(a) > len(b) outputs matrix M such that M * (a, b) = (c, d) are consecutive
+/// remainders of the euclidean sequence with len(d) <= len(a)/2 < len(c).
+/// Only the top halves of a and b are used, which makes it O(M(n)log n)
+fn half_gcd<F: FftField>(a: &DensePolynomial<F>, b: &DensePolynomial<F>) -> PolyMatrix<F> {
+    debug_assert!(len(a) > len(b));
+    let k = len(a) / 2;
+    if len(b) <= k {
+        return PolyMatrix::identity();
+    }
+
+    let m1 = half_gcd(&shift_down(a, k), &shift_down(b, k));
+    let (a, b) = m1.apply(a, b);
+    if len(&b) <= k {
+        return m1;
+    }
+
+    let (q, r) = divide(&a, &b);
+    let m1 = PolyMatrix::euclidean_step(&q).mul(&m1);
+    let (a, b) = (b, r);
+    if len(&b) <= k {
+        return m1;
+    }
+
+    let j = 2 * k - (len(&a) - 1);
+    let m2 = half_gcd(&shift_down(&a, j), &shift_down(&b, j));
+    m2.mul(&m1)
+}
+
+/// Outputs (g, u, v) such that g = gcd(a, b) is monic and u * a + v * b = g,
+/// uses half-gcd so it runs in O(M(n)log n).
+/// For a = b = 0 outputs zero polynomials
+pub fn xgcd<F: FftField>(
+    a: &DensePolynomial<F>,
+    b: &DensePolynomial<F>,
+) -> (DensePolynomial<F>, DensePolynomial<F>, DensePolynomial<F>) {
+    if len(a) < len(b) {
+        let (g, v, u) = xgcd(b, a);
+        return (g, u, v);
+    }
+
+    // invariant: m * (a, b) = (r0, r1)
+    let mut m = PolyMatrix::identity();
+    let (mut r0, mut r1) = (a.clone(), b.clone());
+    while !r1.is_zero() {
+        // half-gcd expects deg(r0) > deg(r1), which fails only for deg(a) = deg(b)
+        if len(&r0) > len(&r1) {
+            let m1 = half_gcd(&r0, &r1);
+            (r0, r1) = m1.apply(&r0, &r1);
+            m = m1.mul(&m);
+            if r1.is_zero() {
+                break;
+            }
+        }
+
+        let (q, r) = divide(&r0, &r1);
+        m = PolyMatrix::euclidean_step(&q).mul(&m);
+        (r0, r1) = (r1, r);
+    }
+
+    if r0.is_zero() {
+        return (r0, DensePolynomial::zero(), DensePolynomial::zero());
+    }
+
+    let lc_inv = r0.coeffs.last().unwrap().inverse().unwrap();
+    let [[u, v], _] = m.0;
+    (&r0 * lc_inv, &u * lc_inv, &v * lc_inv)
+}
+
+/// Computes the (m, k) Pade approximant of the power series given by its first m + k + 1
+/// coefficients (further coefficients are ignored), i.e. (p, q) with deg(p) <= m,
+/// deg(q) <= k, q(0) = 1 and p = q * series mod X^(m + k + 1).
+/// Runs the half-gcd on (X^(m + k + 1), series) until the first remainder of degree <= m
+pub fn pade<F: FftField>(
+    series: &DensePolynomial<F>,
+    m: usize,
+    k: usize,
+) -> Result<(DensePolynomial<F>, DensePolynomial<F>), Error> {
+    let l = m + k + 1;
+    let mut x_l = vec![F::zero(); l];
+    x_l.push(F::one());
+    let x_l = DensePolynomial::from_coefficients_vec(x_l);
+    let series = DensePolynomial::from_coefficients_slice(&series.coeffs[..len(series).min(l)]);
+
+    // invariant: mat * (X^l, series) = (r0, r1)
+    let mut mat = PolyMatrix::identity();
+    let (mut r0, mut r1) = (x_l, series);
+    while len(&r1) > m + 1 {
+        // quotients computed from the top parts are valid for the whole remainders and
+        // half-gcd stops with len(r0) > m + 1, so the first remainder of degree <= m is not skipped
+        let shift = (2 * (m + 1)).saturating_sub(len(&r0));
+        let m1 = half_gcd(&shift_down(&r0, shift), &shift_down(&r1, shift));
+        (r0, r1) = m1.apply(&r0, &r1);
+        mat = m1.mul(&mat);
+        if len(&r1) <= m + 1 {
+            break;
+        }
+
+        let (q, r) = divide(&r0, &r1);
+        mat = PolyMatrix::euclidean_step(&q).mul(&mat);
+        (r0, r1) = (r1, r);
+    }
+
+    let [_, [_, q]] = mat.0;
+    let q0 = q.coeffs.first().copied().unwrap_or_else(F::zero);
+    if q0.is_zero() {
+        return Err(Error::NoPadeApproximant);
+    }
+
+    let q0_inv = q0.inverse().unwrap();
+    Ok((&r1 * q0_inv, &q * q0_inv))
+}
+
+#[cfg(test)]
+mod gcd_tests {
+    use ark_bn254::Fr;
+    use ark_ff::{Field, One, UniformRand, Zero};
+    use ark_poly::{univariate::DensePolynomial, Polynomial, UVPolynomial};
+    use ark_std::test_rng;
+
+    use crate::{
+        error::Error,
+        fast_eval::FastEval,
+        gcd::{pade, xgcd},
+    };
+
+    /// first l coefficients of p/q
+    fn series_of(p: &DensePolynomial<Fr>, q: &DensePolynomial<Fr>, l: usize) -> Vec<Fr> {
+        let q0_inv = q.coeffs[0].inverse().unwrap();
+        let mut series = Vec::with_capacity(l);
+        for i in 0..l {
+            let mut si = p.coeffs.get(i).copied().unwrap_or_else(Fr::zero);
+            for j in 1..=i.min(q.degree()) {
+                si -= q.coeffs[j] * series[i - j];
+            }
+            series.push(si * q0_inv);
+        }
+        series
+    }
+
+    #[test]
+    fn test_xgcd() {
+        let mut rng = test_rng();
+
+        for (deg_g, deg_a, deg_b) in [(0, 10, 7), (5, 20, 20), (17, 3, 40), (1, 64, 31)] {
+            let mut g = DensePolynomial::<Fr>::rand(deg_g, &mut rng);
+            g = &g * g.coeffs.last().unwrap().inverse().unwrap();
+            let a = &g * &DensePolynomial::rand(deg_a, &mut rng);
+            let b = &g * &DensePolynomial::rand(deg_b, &mut rng);
+
+            let (gcd, u, v) = xgcd(&a, &b);
+            assert_eq!(gcd, g);
+            assert_eq!(&(&u * &a) + &(&v * &b), gcd);
+        }
+
+        let a = DensePolynomial::<Fr>::rand(10, &mut rng);
+        let (gcd, u, v) = xgcd(&a, &DensePolynomial::zero());
+        assert_eq!(&u * &a, gcd);
+        assert!(v.is_zero());
+        assert_eq!(*gcd.coeffs.last().unwrap(), Fr::one());
+    }
+
+    #[test]
+    fn test_pade() {
+        let mut rng = test_rng();
+
+        for (m, k) in [(0, 0), (3, 0), (0, 3), (5, 5), (10, 3), (3, 10), (20, 17)] {
+            let p = DensePolynomial::<Fr>::rand(m, &mut rng);
+            let mut q = DensePolynomial::<Fr>::rand(k, &mut rng);
+            q = &q * q.coeffs[0].inverse().unwrap();
+
+            let series = series_of(&p, &q, m + k + 1);
+            let (p_approx, q_approx) =
+                pade(&DensePolynomial::from_coefficients_vec(series), m, k).unwrap();
+            assert_eq!(p_approx, p);
+            assert_eq!(q_approx, q);
+        }
+
+        // arbitrary series, approximant defined by its congruence
+        for (m, k) in [(4, 4), (7, 2), (2, 7), (16, 16)] {
+            let series = DensePolynomial::<Fr>::rand(m + k + 5, &mut rng);
+            let (p, q) = pade(&series, m, k).unwrap();
+            assert!(p.degree() <= m);
+            assert!(q.degree() <= k);
+            assert_eq!(q.coeffs[0], Fr::one());
+
+            let (_, r) =
+                FastEval::fast_divide_with_q_and_r(&(&(&q * &series) - &p), &x_pow(m + k + 1))
+                    .unwrap();
+            assert!(r.is_zero());
+        }
+
+        // 1 + X^2 has no (1, 1) approximant with q(0) != 0
+        let series = DensePolynomial::from_coefficients_slice(&[Fr::one(), Fr::zero(), Fr::one()]);
+        assert_eq!(pade(&series, 1, 1).err(), Some(Error::NoPadeApproximant));
+
+        let alpha = Fr::rand(&mut rng);
+        let one = DensePolynomial::from_coefficients_slice(&[Fr::one()]);
+        let (p, q) = pade(&one, 2, 2).unwrap();
+        assert_eq!(p.evaluate(&alpha), q.evaluate(&alpha));
+    }
+
+    fn x_pow(l: usize) -> DensePolynomial<Fr> {
+        let mut coeffs = vec![Fr::zero(); l];
+        coeffs.push(Fr::one());
+        DensePolynomial::from_coefficients_vec(coeffs)
+    }
+}
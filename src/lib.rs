@@ -1,27 +1,344 @@
 use std::marker::PhantomData;
 
-use ark_ff::FftField;
-use ark_poly::{univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain};
+use ark_ff::{batch_inversion, FftField, Zero};
+use ark_poly::{
+    univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain, Polynomial,
+};
 use error::Error;
+use fast_eval::FastEval;
 use fft::FftProcessor;
+use gcd::{xgcd, RootsCertificate};
 
 pub use crate::subtree::Pow2ProductSubtree;
 
+pub mod cache;
 pub mod error;
+pub mod extension;
 pub mod fast_eval;
+#[cfg(feature = "ff")]
+pub mod ff_compat;
 pub mod fft;
+pub mod gcd;
+pub mod pairwise;
+pub mod parallel;
+pub mod permutation;
+pub mod pointwise;
+pub mod quotient;
+pub mod stream;
 pub mod subtree;
+pub mod tasks;
+#[cfg(test)]
+mod test_fields;
+
+/// How caller supplied weights enter `interpolate_weighted`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WeightMode {
+    /// sum_i w_i * ri * f_i * zH(X)/(X - x_i), i.e. interpolation of w_i * f_i
+    Compose,
+    /// sum_i w_i * f_i * zH(X)/(X - x_i), the weights stand in for ri
+    Replace,
+}
+
+/// `Sync` so that any processor, including `Box<dyn PolyProcessor<F>>`, can be shared with the
+/// worker threads of the `parallel` feature
+pub trait PolyProcessor<F: FftField>: Sync {
+    fn size(&self) -> usize;
+
+    fn elements(&self) -> Vec<F>;
 
-pub trait PolyProcessor<F: FftField> {
     fn get_vanishing(&self) -> DensePolynomial<F>;
 
+    /// zH(point) without cloning the vanishing polynomial
+    fn evaluate_vanishing_at(&self, point: &F) -> F;
+
+    fn evaluate_vanishing_at_many(&self, points: &[F]) -> Vec<F> {
+        points
+            .iter()
+            .map(|point| self.evaluate_vanishing_at(point))
+            .collect()
+    }
+
     fn get_ri(&self) -> Vec<F>;
 
+    /// zH'(x_i), i.e. the inverses of `get_ri`
+    fn get_vanishing_derivative_evals(&self) -> Vec<F>;
+
     fn evaluate_over_domain(&self, f: &DensePolynomial<F>) -> Vec<F>;
 
+    /// Evaluations over the domain followed by the evaluations at `extra_points`,
+    /// the few extra points are handled with Horner
+    fn evaluate_over_domain_ext(&self, f: &DensePolynomial<F>, extra_points: &[F]) -> Vec<F> {
+        let mut evals = self.evaluate_over_domain(f);
+        evals.extend(extra_points.iter().map(|point| f.evaluate(point)));
+        evals
+    }
+
+    /// Outputs evaluations of f, f', ..., f^(k) over the domain, indexed as `[j][i]` = f^(j)(x_i)
+    fn evaluate_derivatives_over_domain(&self, f: &DensePolynomial<F>, k: usize) -> Vec<Vec<F>>;
+
     fn interpolate(&self, evals: &[F]) -> DensePolynomial<F>;
 
+    /// sum_i w_i * f(x_i), for callers which only need the scalar
+    fn weighted_sum(&self, f: &DensePolynomial<F>, weights: &[F]) -> F {
+        assert_eq!(weights.len(), self.size());
+        self.evaluate_over_domain(f)
+            .iter()
+            .zip(weights.iter())
+            .map(|(&f_i, &w_i)| f_i * w_i)
+            .sum()
+    }
+
+    /// zH/(X - x_i) for all i. The output alone has n^2 coefficients,
+    /// each quotient costs one O(n) synthetic division of the shared zH
+    fn vanishing_quotients(&self) -> Vec<DensePolynomial<F>> {
+        let vanishing = self.get_vanishing();
+        self.elements()
+            .iter()
+            .map(|x_i| FastEval::divide_by_monomial(&vanishing, x_i))
+            .collect()
+    }
+
+    /// Evaluations of zH/(X - x_i) over the domain of `other`, indexed as `[i][j]` for y_j.
+    /// zH is evaluated over Y with a single descent and combined with the inverse
+    /// differences 1/(y_j - x_i), batch inverted at once. At y_j in H the quotients are
+    /// zH'(x_i) for y_j = x_i and zero otherwise
+    fn vanishing_quotients_over(&self, other: &dyn PolyProcessor<F>) -> Vec<Vec<F>> {
+        let xs = self.elements();
+        let ys = other.elements();
+        let zh_evals =
+            other.evaluate_over_domain(&other.reduce_mod_vanishing(&self.get_vanishing()));
+        let vanishing_derivative_evals = self.get_vanishing_derivative_evals();
+
+        let mut diffs_inv = Vec::with_capacity(xs.len() * ys.len());
+        for x_i in &xs {
+            for (y_j, zh_j) in ys.iter().zip(zh_evals.iter()) {
+                // y_j in H are resolved separately
+                diffs_inv.push(if zh_j.is_zero() { F::one() } else { *y_j - x_i });
+            }
+        }
+        batch_inversion(&mut diffs_inv);
+
+        let m = ys.len();
+        xs.iter()
+            .enumerate()
+            .zip(vanishing_derivative_evals.iter())
+            .map(|((i, x_i), zh_prime_i)| {
+                let row = &diffs_inv[i * m..(i + 1) * m];
+                ys.iter()
+                    .zip(zh_evals.iter())
+                    .zip(row.iter())
+                    .map(
+                        |((y_j, zh_j), diff_inv)| match (zh_j.is_zero(), y_j == x_i) {
+                            (false, _) => *zh_j * diff_inv,
+                            (true, true) => *zh_prime_i,
+                            (true, false) => F::zero(),
+                        },
+                    )
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Coefficient forms of all the Lagrange basis polynomials Li = ri * zH/(X - x_i),
+    /// O(n^2) which is the size of the output
+    fn all_lagrange_coefficients(&self) -> Vec<DensePolynomial<F>> {
+        self.vanishing_quotients()
+            .iter()
+            .zip(self.get_ri().iter())
+            .map(|(quotient, ri)| quotient * *ri)
+            .collect()
+    }
+
+    /// Interpolation with per point weights composed with or replacing the barycentric ri,
+    /// for normalized or rescaled Lagrange bases
+    fn interpolate_weighted(
+        &self,
+        evals: &[F],
+        weights: &[F],
+        mode: WeightMode,
+    ) -> DensePolynomial<F> {
+        assert_eq!(evals.len(), weights.len());
+        let weighted: Vec<F> = match mode {
+            WeightMode::Compose => evals
+                .iter()
+                .zip(weights.iter())
+                .map(|(&f_i, &w_i)| f_i * w_i)
+                .collect(),
+            // w_i / ri = w_i * zH'(x_i)
+            WeightMode::Replace => evals
+                .iter()
+                .zip(weights.iter())
+                .zip(self.get_vanishing_derivative_evals().iter())
+                .map(|((&f_i, &w_i), &zh_prime_i)| f_i * w_i * zh_prime_i)
+                .collect(),
+        };
+        self.interpolate(&weighted)
+    }
+
+    /// f mod zH, domains with a sparse vanishing polynomial override it with a linear time fold
+    fn reduce_mod_vanishing(&self, f: &DensePolynomial<F>) -> DensePolynomial<F> {
+        let (_, r) = FastEval::fast_divide_with_q_and_r(f, &self.get_vanishing()).unwrap();
+        r
+    }
+
     fn batch_evaluate_lagrange_basis(&self, point: &F) -> Vec<F>;
+
+    /// Interpolates the evaluations only if the interpolant has degree < d,
+    /// i.e. the coefficients of X^d..X^(n-1) are all zero, otherwise fails with its degree
+    fn interpolate_with_degree_bound(
+        &self,
+        evals: &[F],
+        d: usize,
+    ) -> Result<DensePolynomial<F>, Error> {
+        let f = self.interpolate(evals);
+        if f.coeffs.len() > d {
+            return Err(Error::DegreeBoundExceeded(f.degree()));
+        }
+        Ok(f)
+    }
+
+    /// Multiplies two polynomials of degree < n given by their evaluations over the domain.
+    /// Pointwise products over the domain only determine the product mod zH, so the
+    /// evaluations are returned over an extension of at least 2n - 1 points: `extension` if
+    /// given, otherwise the multiplicative subgroup of size 2n (in `elements()` order),
+    /// failing with `NoSubgroupOfSize` if the field has none
+    fn multiply_evals(
+        &self,
+        a_evals: &[F],
+        b_evals: &[F],
+        extension: Option<&dyn PolyProcessor<F>>,
+    ) -> Result<Vec<F>, Error> {
+        let n = self.size();
+        let a = self.interpolate(a_evals);
+        let b = self.interpolate(b_evals);
+
+        let evaluate_product = |extension: &dyn PolyProcessor<F>| {
+            let a_evals = extension.evaluate_over_domain(&a);
+            let b_evals = extension.evaluate_over_domain(&b);
+            a_evals
+                .iter()
+                .zip(b_evals.iter())
+                .map(|(&a, &b)| a * b)
+                .collect()
+        };
+
+        match extension {
+            Some(extension) => {
+                if extension.size() < 2 * n - 1 {
+                    return Err(Error::ExtensionTooSmall);
+                }
+                Ok(evaluate_product(extension))
+            }
+            None => {
+                let domain = GeneralEvaluationDomain::<F>::new(2 * n)
+                    .ok_or(Error::NoSubgroupOfSize(2 * n))?;
+                let extension = FftProcessor::<F>::construct(domain)?;
+                Ok(evaluate_product(&extension))
+            }
+        }
+    }
+
+    /// Pointwise quotient num(x_i)/den(x_i) with a single batch inversion,
+    /// fails with the indices where den vanishes
+    fn divide_evals(&self, num: &[F], den: &[F]) -> Result<Vec<F>, Error> {
+        assert_eq!(num.len(), den.len());
+
+        let zeros: Vec<_> = den
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| d.is_zero())
+            .map(|(i, _)| i)
+            .collect();
+        if !zeros.is_empty() {
+            return Err(Error::ZeroDenominator(zeros));
+        }
+
+        let mut den_inv = den.to_vec();
+        batch_inversion(&mut den_inv);
+
+        Ok(num
+            .iter()
+            .zip(den_inv.iter())
+            .map(|(&num_i, &den_inv_i)| num_i * den_inv_i)
+            .collect())
+    }
+
+    /// Same as `divide_evals` but additionally checks that the interpolant of den divides
+    /// the interpolant of num, so the result are evaluations of an actual polynomial quotient
+    fn divide_evals_exact(&self, num: &[F], den: &[F]) -> Result<Vec<F>, Error> {
+        let quotient_evals = self.divide_evals(num, den)?;
+
+        let num_poly = self.interpolate(num);
+        let den_poly = self.interpolate(den);
+        let (_, r) = FastEval::fast_divide_with_q_and_r(&num_poly, &den_poly).unwrap();
+        if !r.is_zero() {
+            return Err(Error::NonZeroRemainder);
+        }
+
+        Ok(quotient_evals)
+    }
+
+    /// For f of degree < n given by evaluations and a subset S of domain indices, outputs the
+    /// evaluations of f/zS over the whole domain.
+    /// f must vanish on S, otherwise fails with the indices of S where it doesn't;
+    /// on S the quotient is resolved with l'Hopital's rule: (f/zS)(x_i) = f'(x_i)/zS'(x_i)
+    fn divide_by_subset_vanishing(&self, evals: &[F], subset: &[usize]) -> Result<Vec<F>, Error> {
+        let n = self.size();
+        assert_eq!(evals.len(), n);
+
+        let mut in_subset = vec![false; n];
+        for &i in subset {
+            assert!(!in_subset[i], "Index {} appears twice in subset", i);
+            in_subset[i] = true;
+        }
+
+        let not_vanishing: Vec<_> = subset
+            .iter()
+            .filter(|&&i| !evals[i].is_zero())
+            .copied()
+            .collect();
+        if !not_vanishing.is_empty() {
+            return Err(Error::NotVanishingOnSubset(not_vanishing));
+        }
+
+        // f of degree < n vanishing on the whole domain is zero
+        if subset.len() == n {
+            return Ok(vec![F::zero(); n]);
+        }
+
+        let elements = self.elements();
+        let subset_roots: Vec<_> = subset.iter().map(|&i| elements[i]).collect();
+        let zs = FastEval::product_of_monomials(&subset_roots);
+
+        let mut num = evals.to_vec();
+        let mut den = self.evaluate_over_domain(&zs);
+
+        if !subset.is_empty() {
+            let f = self.interpolate(evals);
+            let f_prime_evals = self.evaluate_over_domain(&FastEval::derivative(&f));
+            let zs_prime_evals = self.evaluate_over_domain(&FastEval::derivative(&zs));
+            for &i in subset {
+                num[i] = f_prime_evals[i];
+                den[i] = zs_prime_evals[i];
+            }
+        }
+
+        self.divide_evals(&num, &den)
+    }
+
+    /// Deterministically checks that zH has no repeated roots by computing gcd(zH, zH')
+    /// with the half-gcd, outputs either the bezout coefficients or the repeated factor
+    fn certify_distinct_roots(&self) -> RootsCertificate<F> {
+        let vanishing = self.get_vanishing();
+        let vanishing_derivative = FastEval::derivative(&vanishing);
+
+        let (g, u, v) = xgcd(&vanishing, &vanishing_derivative);
+        if g.degree() == 0 {
+            RootsCertificate::Distinct { u, v }
+        } else {
+            RootsCertificate::Repeated(g)
+        }
+    }
 }
 
 pub struct PolyProcessorStrategy<F: FftField> {
@@ -0,0 +1,120 @@
+use ark_ff::{batch_inversion, FftField};
+
+use crate::{error::Error, fast_eval::FastEval, PolyProcessor};
+
+/// Outputs `grid[i][j]` = 1/(x_i - y_j), all n * m differences are inverted with a single batch
+/// inversion. Fails with the index pairs (i, j) where x_i = y_j
+pub fn inverse_difference_grid<F: FftField>(xs: &[F], ys: &[F]) -> Result<Vec<Vec<F>>, Error> {
+    let mut common_points = vec![];
+    let mut diffs = Vec::with_capacity(xs.len() * ys.len());
+    for (i, x_i) in xs.iter().enumerate() {
+        for (j, y_j) in ys.iter().enumerate() {
+            if x_i == y_j {
+                common_points.push((i, j));
+            }
+            diffs.push(*x_i - y_j);
+        }
+    }
+
+    if !common_points.is_empty() {
+        return Err(Error::CommonPoints(common_points));
+    }
+
+    batch_inversion(&mut diffs);
+    let m = ys.len();
+    Ok((0..xs.len())
+        .map(|i| diffs[i * m..(i + 1) * m].to_vec())
+        .collect())
+}
+
+/// Row and column sums of the inverse difference grid without materializing it:
+/// sum_j 1/(x_i - y_j) = zY'(x_i)/zY(x_i) and sum_i 1/(x_i - y_j) = -zX'(y_j)/zX(y_j),
+/// so each side costs one descent down the tree of the other domain.
+/// Fails with `ZeroDenominator` listing the x_i which are also in Y
+pub fn inverse_difference_sums<F: FftField>(
+    x_processor: &dyn PolyProcessor<F>,
+    y_processor: &dyn PolyProcessor<F>,
+) -> Result<(Vec<F>, Vec<F>), Error> {
+    let row_sums = log_derivative_evals(x_processor, y_processor)?;
+    // no x_i is in Y at this point, so no y_j is in X either
+    let column_sums = log_derivative_evals(y_processor, x_processor)?
+        .into_iter()
+        .map(|sum| -sum)
+        .collect();
+
+    Ok((row_sums, column_sums))
+}
+
+/// Evaluations of zB'/zB over domain A
+fn log_derivative_evals<F: FftField>(
+    a_processor: &dyn PolyProcessor<F>,
+    b_processor: &dyn PolyProcessor<F>,
+) -> Result<Vec<F>, Error> {
+    let zb = b_processor.get_vanishing();
+    let zb_prime = FastEval::derivative(&zb);
+
+    // vanishing of B can be longer than domain A, only its residue mod zA matters
+    let zb = a_processor.reduce_mod_vanishing(&zb);
+    let zb_prime = a_processor.reduce_mod_vanishing(&zb_prime);
+
+    let zb_evals = a_processor.evaluate_over_domain(&zb);
+    let zb_prime_evals = a_processor.evaluate_over_domain(&zb_prime);
+    a_processor.divide_evals(&zb_prime_evals, &zb_evals)
+}
+
+#[cfg(test)]
+mod pairwise_tests {
+    use ark_bn254::Fr;
+    use ark_ff::{Field, UniformRand, Zero};
+    use ark_std::test_rng;
+
+    use crate::{
+        error::Error,
+        pairwise::{inverse_difference_grid, inverse_difference_sums},
+        subtree::Pow2ProductSubtree,
+    };
+
+    #[test]
+    fn test_inverse_difference_grid() {
+        let mut rng = test_rng();
+        let xs: Vec<_> = (0..16).map(|_| Fr::rand(&mut rng)).collect();
+        let ys: Vec<_> = (0..64).map(|_| Fr::rand(&mut rng)).collect();
+
+        let grid = inverse_difference_grid(&xs, &ys).unwrap();
+        for (x_i, row) in xs.iter().zip(grid.iter()) {
+            for (y_j, grid_ij) in ys.iter().zip(row.iter()) {
+                assert_eq!(*grid_ij, (*x_i - y_j).inverse().unwrap());
+            }
+        }
+
+        let x_processor = Pow2ProductSubtree::construct(&xs).unwrap();
+        let y_processor = Pow2ProductSubtree::construct(&ys).unwrap();
+        let (row_sums, column_sums) = inverse_difference_sums(&x_processor, &y_processor).unwrap();
+        for (i, row) in grid.iter().enumerate() {
+            let sum = row.iter().fold(Fr::zero(), |acc, v| acc + v);
+            assert_eq!(row_sums[i], sum);
+        }
+        for (j, column_sum) in column_sums.iter().enumerate() {
+            let sum = grid.iter().fold(Fr::zero(), |acc, row| acc + row[j]);
+            assert_eq!(*column_sum, sum);
+        }
+
+        // one empty row per x_i
+        let grid = inverse_difference_grid(&xs, &[]).unwrap();
+        assert_eq!(grid, vec![vec![]; xs.len()]);
+
+        let mut ys = ys;
+        ys[5] = xs[3];
+        ys[40] = xs[3];
+        assert_eq!(
+            inverse_difference_grid(&xs, &ys).err(),
+            Some(Error::CommonPoints(vec![(3, 5), (3, 40)]))
+        );
+
+        let y_processor = Pow2ProductSubtree::construct(&ys).unwrap();
+        assert_eq!(
+            inverse_difference_sums(&x_processor, &y_processor).err(),
+            Some(Error::ZeroDenominator(vec![3]))
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Glue for the `parallel` feature, without it everything runs on the calling thread.
+//!
+//! Work is scheduled on rayon's global thread pool. On `wasm32` that pool has to be backed by
+//! web workers sharing the module memory through a `SharedArrayBuffer`, which only the
+//! embedder can spawn: build with `-C target-feature=+atomics,+bulk-memory,+mutable-globals`
+//! and initialize the pool before calling into the crate, either with `initThreadPool` from
+//! `wasm-bindgen-rayon` or with `init_thread_pool` and a worker spawner of your own.
+//! Otherwise rayon falls back to a single thread, [`ensure_parallel`] turns that into an error.
+
+use crate::error::Error;
+
+/// Runs both closures, potentially in parallel
+pub fn join<A, B, RA, RB>(oper_a: A, oper_b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        rayon::join(oper_a, oper_b)
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        (oper_a(), oper_b())
+    }
+}
+
+/// Number of threads the tree algorithms are spread over. With `parallel`, called outside a
+/// worker thread this builds rayon's default global pool if none exists yet, after which
+/// `init_thread_pool` fails, so a custom pool has to be initialized first
+pub fn num_threads() -> usize {
+    #[cfg(feature = "parallel")]
+    {
+        rayon::current_num_threads()
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        1
+    }
+}
+
+/// Calls `consume(i, produce(i))` for i in 0..len in order. With more than one thread the items
+/// are produced on the pool, at most one ahead of `consume` which runs on the calling thread.
+/// The calling thread blocks while waiting, so call it from outside the pool. Builds the default
+/// pool if none exists, like `num_threads`
+pub fn pipeline<T, P, C>(len: usize, produce: P, mut consume: C)
+where
+    T: Send,
+    P: Fn(usize) -> T + Sync,
+    C: FnMut(usize, T),
+{
+    #[cfg(feature = "parallel")]
+    {
+        if num_threads() > 1 {
+            use std::sync::mpsc::sync_channel;
+
+            // a rendezvous channel: the producer holds the next item until `consume` takes it
+            let (sender, receiver) = sync_channel::<(usize, T)>(0);
+            let produce = &produce;
+            rayon::in_place_scope(|scope| {
+                scope.spawn(move |_| {
+                    for i in 0..len {
+                        // the receiver only hangs up if `consume` panicked
+                        if sender.send((i, produce(i))).is_err() {
+                            return;
+                        }
+                    }
+                });
+
+                for (i, item) in receiver {
+                    consume(i, item);
+                }
+            });
+            return;
+        }
+    }
+
+    for i in 0..len {
+        consume(i, produce(i));
+    }
+}
+
+/// Builds rayon's global pool with `num_threads` threads, `spawn_thread` has to call
+/// `ThreadBuilder::run` on a new thread. On `wasm32` that's a web worker instantiated with the
+/// module and memory of the caller, the same hook `wasm-bindgen-rayon` plugs its workers into.
+/// Fails if the global pool is already built, so it has to run before the first parallel call.
+/// That includes `num_threads`, `ensure_parallel` and `stream::interpolate_into`, which all build
+/// the default pool; rayon has no way to check for a pool without building one
+#[cfg(feature = "parallel")]
+pub fn init_thread_pool<S>(
+    num_threads: usize,
+    spawn_thread: S,
+) -> Result<(), rayon::ThreadPoolBuildError>
+where
+    S: FnMut(rayon::ThreadBuilder) -> std::io::Result<()>,
+{
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .spawn_handler(spawn_thread)
+        .build_global()
+}
+
+/// Outputs the number of threads, failing with `SingleThreaded` when work would run on the
+/// calling thread only: without the `parallel` feature, or when rayon fell back to a single
+/// thread, e.g. on `wasm32` without an initialized pool. Like `num_threads` it builds the default
+/// pool if none exists, so call `init_thread_pool` before it
+pub fn ensure_parallel() -> Result<usize, Error> {
+    match num_threads() {
+        1 => Err(Error::SingleThreaded),
+        threads => Ok(threads),
+    }
+}
+
+#[cfg(test)]
+mod parallel_tests {
+    use crate::{
+        error::Error,
+        parallel::{ensure_parallel, num_threads, pipeline},
+    };
+
+    #[cfg(feature = "parallel")]
+    use crate::parallel::init_thread_pool;
+
+    #[test]
+    fn test_ensure_parallel() {
+        match ensure_parallel() {
+            Ok(threads) => assert_eq!(threads, num_threads()),
+            Err(err) => {
+                assert_eq!(err, Error::SingleThreaded);
+                assert_eq!(num_threads(), 1);
+            }
+        }
+
+        #[cfg(not(feature = "parallel"))]
+        assert_eq!(ensure_parallel(), Err(Error::SingleThreaded));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_init_thread_pool_after_default_pool() {
+        // the default pool is built by the first query
+        num_threads();
+        let spawn_thread = |thread: rayon::ThreadBuilder| {
+            std::thread::Builder::new()
+                .spawn(|| thread.run())
+                .map(|_| ())
+        };
+        assert!(init_thread_pool(2, spawn_thread).is_err());
+    }
+
+    #[test]
+    fn test_pipeline() {
+        let mut consumed = vec![];
+        pipeline(10, |i| i * i, |i, square| consumed.push((i, square)));
+        assert_eq!(consumed, (0..10).map(|i| (i, i * i)).collect::<Vec<_>>());
+    }
+}
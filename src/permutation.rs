@@ -0,0 +1,155 @@
+//! PLONK-style σ polynomials for copy constraints over an arbitrary domain H.
+//!
+//! Wire (c, i) sits in column c at row i and is labelled k_c * x_i with the coset tag k_c of
+//! its column, the tags must make the cosets k_c * H pairwise disjoint. For a permutation of
+//! the wires, σ_c(x_i) is the label of the wire which (c, i) is mapped to.
+
+use std::collections::{HashMap, HashSet};
+
+use ark_ff::FftField;
+use ark_poly::univariate::DensePolynomial;
+
+use crate::{error::Error, PolyProcessor};
+
+/// (column, row)
+pub type Wire = (usize, usize);
+
+/// Checks that the cosets k_c * H are pairwise disjoint,
+/// fails with the pairs of columns (c, d), c < d, whose cosets share a label
+pub fn check_coset_tags<F: FftField>(elements: &[F], coset_tags: &[F]) -> Result<(), Error> {
+    let mut owners = HashMap::with_capacity(elements.len() * coset_tags.len());
+    let mut overlapping = HashSet::new();
+    for (c, k_c) in coset_tags.iter().enumerate() {
+        for x_i in elements {
+            if let Some(&d) = owners.get(&(*k_c * x_i)) {
+                // d <= c since columns are visited in order
+                if d != c {
+                    overlapping.insert((d, c));
+                }
+            } else {
+                owners.insert(*k_c * x_i, c);
+            }
+        }
+    }
+
+    if overlapping.is_empty() {
+        Ok(())
+    } else {
+        let mut overlapping: Vec<_> = overlapping.into_iter().collect();
+        overlapping.sort_unstable();
+        Err(Error::OverlappingCosets(overlapping))
+    }
+}
+
+/// Evaluations of σ_c over the domain for every column, `permutation[c][i]` is the wire
+/// which (c, i) is mapped to and must describe a bijection of the m * n wires
+pub fn sigma_evals<F: FftField>(
+    processor: &dyn PolyProcessor<F>,
+    coset_tags: &[F],
+    permutation: &[Vec<Wire>],
+) -> Result<Vec<Vec<F>>, Error> {
+    let n = processor.size();
+    let m = coset_tags.len();
+    assert_eq!(permutation.len(), m);
+
+    let elements = processor.elements();
+    check_coset_tags(&elements, coset_tags)?;
+
+    let mut hit = vec![vec![false; n]; m];
+    for column in permutation {
+        assert_eq!(column.len(), n);
+        for &(c, i) in column {
+            if c >= m || i >= n || hit[c][i] {
+                return Err(Error::InvalidPermutation);
+            }
+            hit[c][i] = true;
+        }
+    }
+
+    Ok(permutation
+        .iter()
+        .map(|column| {
+            column
+                .iter()
+                .map(|&(c, i)| coset_tags[c] * elements[i])
+                .collect()
+        })
+        .collect())
+}
+
+/// σ_c in coefficient form, interpolated from `sigma_evals`
+pub fn sigma_polynomials<F: FftField>(
+    processor: &dyn PolyProcessor<F>,
+    coset_tags: &[F],
+    permutation: &[Vec<Wire>],
+) -> Result<Vec<DensePolynomial<F>>, Error> {
+    let evals = sigma_evals(processor, coset_tags, permutation)?;
+    Ok(evals
+        .iter()
+        .map(|evals| processor.interpolate(evals))
+        .collect())
+}
+
+#[cfg(test)]
+mod permutation_tests {
+    use ark_bn254::Fr;
+    use ark_ff::UniformRand;
+    use ark_poly::Polynomial;
+    use ark_std::test_rng;
+
+    use crate::{
+        error::Error,
+        permutation::{sigma_evals, sigma_polynomials, Wire},
+        subtree::Pow2ProductSubtree,
+        PolyProcessor,
+    };
+
+    #[test]
+    fn test_sigma_polynomials() {
+        let n: usize = 16;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+        let coset_tags = vec![Fr::from(1u64), Fr::rand(&mut rng), Fr::rand(&mut rng)];
+        let m = coset_tags.len();
+
+        let permutation: Vec<Vec<Wire>> = (0..m)
+            .map(|c| (0..n).map(|i| ((c + 1) % m, (i + 5) % n)).collect())
+            .collect();
+
+        let sigmas = sigma_polynomials(&subtree, &coset_tags, &permutation).unwrap();
+        for (c, sigma) in sigmas.iter().enumerate() {
+            assert!(sigma.degree() < n);
+            for (i, root) in roots.iter().enumerate() {
+                let (c_to, i_to) = permutation[c][i];
+                assert_eq!(sigma.evaluate(root), coset_tags[c_to] * roots[i_to]);
+            }
+        }
+
+        // second coset contains x_3 since its tag maps x_2 onto it
+        let mut bad_tags = coset_tags.clone();
+        bad_tags[1] = roots[3] / roots[2];
+        assert_eq!(
+            sigma_evals(&subtree, &bad_tags, &permutation).err(),
+            Some(Error::OverlappingCosets(vec![(0, 1)]))
+        );
+
+        let mut bad_permutation = permutation.clone();
+        bad_permutation[2][7] = bad_permutation[0][0];
+        assert_eq!(
+            sigma_evals(&subtree, &coset_tags, &bad_permutation).err(),
+            Some(Error::InvalidPermutation)
+        );
+
+        // identity permutation gives the wire labels
+        let identity: Vec<Vec<Wire>> = (0..m).map(|c| (0..n).map(|i| (c, i)).collect()).collect();
+        let evals = sigma_evals(&subtree, &coset_tags, &identity).unwrap();
+        let elements = subtree.elements();
+        for (c, column) in evals.iter().enumerate() {
+            for (i, eval) in column.iter().enumerate() {
+                assert_eq!(*eval, coset_tags[c] * elements[i]);
+            }
+        }
+    }
+}
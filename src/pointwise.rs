@@ -0,0 +1,115 @@
+//! Pointwise stages of the generic processors: leaf monomial evaluation, multiplication of
+//! evaluations by weights, products of FFT outputs and batch inversion.
+
+use ark_ff::Field;
+use ark_std::cfg_chunks_mut;
+
+use crate::parallel::num_threads;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Number of interleaved chains of `batch_inversion`
+const CHAINS: usize = 8;
+
+/// Pointwise product of two slices of the same length
+pub fn mul<F: Field>(a: &[F], b: &[F]) -> Vec<F> {
+    assert_eq!(a.len(), b.len());
+    a.iter().zip(b.iter()).map(|(&a, &b)| a * b).collect()
+}
+
+/// Multiplies every element by the same scalar
+pub fn scale<F: Field>(a: &[F], scalar: &F) -> Vec<F> {
+    a.iter().map(|&a| a * scalar).collect()
+}
+
+/// Outputs point - x_i for all x_i, the evaluations of the leaves X - x_i
+pub fn evaluate_monomials<F: Field>(elements: &[F], point: &F) -> Vec<F> {
+    elements.iter().map(|x_i| *point - x_i).collect()
+}
+
+/// Montgomery batch inversion over `CHAINS` interleaved prefix products, element i is on chain
+/// i mod `CHAINS`, so consecutive multiplications are independent and overlap in the pipeline.
+/// `benches/pointwise.rs` measures it against `ark_ff::batch_inversion`: word-sized fields,
+/// where a multiplication is short, invert 2-3x faster, 256-bit fields about the same. Zeros are left untouched like in
+/// `ark_ff::batch_inversion`, and with `parallel` the slice is split into one part per thread
+pub fn batch_inversion<F: Field>(v: &mut [F]) {
+    let chunk_size = (v.len() / num_threads()).max(1);
+    cfg_chunks_mut!(v, chunk_size).for_each(interleaved_batch_inversion);
+}
+
+fn interleaved_batch_inversion<F: Field>(v: &mut [F]) {
+    // prefix[i] = product of the non zero elements before i on its chain
+    let mut prefix = Vec::with_capacity(v.len());
+    let mut acc = [F::one(); CHAINS];
+    for (i, x_i) in v.iter().enumerate() {
+        prefix.push(acc[i % CHAINS]);
+        if !x_i.is_zero() {
+            acc[i % CHAINS] *= x_i;
+        }
+    }
+
+    // one inversion for all the chains
+    ark_ff::batch_inversion(&mut acc);
+
+    for (i, (x_i, prefix_i)) in v.iter_mut().zip(prefix.iter()).enumerate().rev() {
+        if !x_i.is_zero() {
+            let inverse = acc[i % CHAINS] * prefix_i;
+            acc[i % CHAINS] *= *x_i;
+            *x_i = inverse;
+        }
+    }
+}
+
+#[cfg(test)]
+mod pointwise_tests {
+    use ark_bn254::Fr;
+    use ark_ff::{batch_inversion as ark_batch_inversion, Field};
+    use ark_std::test_rng;
+
+    use crate::{
+        pointwise::{batch_inversion, evaluate_monomials, mul, scale},
+        test_fields::BabyBear,
+    };
+
+    fn check_pointwise<F: Field>() {
+        let mut rng = test_rng();
+        let n = 29;
+
+        let mut a: Vec<_> = (0..n).map(|_| F::rand(&mut rng)).collect();
+        let mut b: Vec<_> = (0..n).map(|_| F::rand(&mut rng)).collect();
+        // extreme values
+        a[0] = -F::one();
+        b[0] = -F::one();
+        a[1] = F::zero();
+        b[2] = -F::one();
+
+        let product: Vec<_> = a.iter().zip(b.iter()).map(|(a, b)| *a * b).collect();
+        assert_eq!(mul(&a, &b), product);
+
+        let scalar = -F::one();
+        let scaled: Vec<_> = a.iter().map(|a| *a * scalar).collect();
+        assert_eq!(scale(&a, &scalar), scaled);
+
+        for point in [F::rand(&mut rng), F::zero(), -F::one()] {
+            let monomials: Vec<_> = a.iter().map(|a| point - a).collect();
+            assert_eq!(evaluate_monomials(&a, &point), monomials);
+        }
+
+        // zeros are skipped, on the first chain and past the last full round of chains
+        let mut v = b.clone();
+        v[0] = F::zero();
+        v[n - 2] = F::zero();
+        let mut expected = v.clone();
+        ark_batch_inversion(&mut expected);
+        batch_inversion(&mut v);
+        assert_eq!(v, expected);
+        assert_eq!(v[3] * b[3], F::one());
+    }
+
+    #[test]
+    fn test_pointwise() {
+        check_pointwise::<Fr>();
+        check_pointwise::<BabyBear>();
+    }
+}
@@ -0,0 +1,137 @@
+//! The "evaluate on an extended domain, divide, interpolate" prover step for arbitrary base
+//! domains: the numerator is evaluated over a domain D disjoint from H, where zH has no zeros,
+//! divided pointwise by zH and the quotient is interpolated back over D.
+
+use ark_ff::{FftField, Zero};
+use ark_poly::{univariate::DensePolynomial, Polynomial};
+
+use crate::{error::Error, PolyProcessor};
+
+/// Evaluations of zH over the extension domain D,
+/// fails with the indices of D which are also in H
+pub fn vanishing_evals_over<F: FftField>(
+    base: &dyn PolyProcessor<F>,
+    ext: &dyn PolyProcessor<F>,
+) -> Result<Vec<F>, Error> {
+    // zH can be longer than D, only its residue mod zD matters
+    let zh = ext.reduce_mod_vanishing(&base.get_vanishing());
+    let zh_evals = ext.evaluate_over_domain(&zh);
+
+    let common: Vec<_> = zh_evals
+        .iter()
+        .enumerate()
+        .filter(|(_, eval)| eval.is_zero())
+        .map(|(i, _)| i)
+        .collect();
+    if !common.is_empty() {
+        return Err(Error::DomainsNotDisjoint(common));
+    }
+    Ok(zh_evals)
+}
+
+/// Interpolates num/zH from the evaluations of num over D.
+/// D must be large enough to determine num (|D| > deg(num)), then the quotient has
+/// degree < |D| - |H| and a higher degree interpolant means num doesn't vanish on H,
+/// in which case fails with `NonZeroRemainder`
+pub fn quotient_from_evals<F: FftField>(
+    base: &dyn PolyProcessor<F>,
+    ext: &dyn PolyProcessor<F>,
+    numerator_evals: &[F],
+) -> Result<DensePolynomial<F>, Error> {
+    assert_eq!(numerator_evals.len(), ext.size());
+    assert!(ext.size() > base.size());
+
+    let zh_evals = vanishing_evals_over(base, ext)?;
+    let quotient_evals = ext.divide_evals(numerator_evals, &zh_evals)?;
+    let quotient = ext.interpolate(&quotient_evals);
+
+    if !quotient.is_zero() && quotient.degree() >= ext.size() - base.size() {
+        return Err(Error::NonZeroRemainder);
+    }
+    Ok(quotient)
+}
+
+/// Evaluates the columns over D, combines them row by row with
+/// `constraint(row_index, column_values)` and outputs the quotient by zH
+pub fn compute_quotient<F: FftField, C: Fn(usize, &[F]) -> F>(
+    base: &dyn PolyProcessor<F>,
+    ext: &dyn PolyProcessor<F>,
+    columns: &[DensePolynomial<F>],
+    constraint: C,
+) -> Result<DensePolynomial<F>, Error> {
+    let columns_evals: Vec<_> = columns
+        .iter()
+        .map(|column| ext.evaluate_over_domain(column))
+        .collect();
+
+    let mut row = vec![F::zero(); columns.len()];
+    let numerator_evals: Vec<_> = (0..ext.size())
+        .map(|i| {
+            for (value, evals) in row.iter_mut().zip(columns_evals.iter()) {
+                *value = evals[i];
+            }
+            constraint(i, &row)
+        })
+        .collect();
+
+    quotient_from_evals(base, ext, &numerator_evals)
+}
+
+#[cfg(test)]
+mod quotient_tests {
+    use ark_bn254::Fr;
+    use ark_ff::{UniformRand, Zero};
+    use ark_poly::{
+        univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain, UVPolynomial,
+    };
+    use ark_std::test_rng;
+
+    use crate::{
+        error::Error, fast_eval::FastEval, fft::FftProcessor, quotient::compute_quotient,
+        subtree::Pow2ProductSubtree, PolyProcessor,
+    };
+
+    #[test]
+    fn test_compute_quotient() {
+        let n: usize = 16;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let base = Pow2ProductSubtree::construct(&roots).unwrap();
+        let domain = GeneralEvaluationDomain::<Fr>::new(4 * n).unwrap();
+        let ext = FftProcessor::construct(domain).unwrap();
+
+        // c interpolates a * b over H, so a * b - c vanishes on H
+        let a = DensePolynomial::<Fr>::rand(n - 1, &mut rng);
+        let b = DensePolynomial::<Fr>::rand(n - 1, &mut rng);
+        let ab_evals: Vec<_> = base
+            .evaluate_over_domain(&a)
+            .iter()
+            .zip(base.evaluate_over_domain(&b).iter())
+            .map(|(a, b)| *a * b)
+            .collect();
+        let c = base.interpolate(&ab_evals);
+
+        let columns = vec![a.clone(), b.clone(), c.clone()];
+        let quotient =
+            compute_quotient(&base, &ext, &columns, |_, row| row[0] * row[1] - row[2]).unwrap();
+
+        let (expected, r) =
+            FastEval::fast_divide_with_q_and_r(&(&(&a * &b) - &c), &base.get_vanishing()).unwrap();
+        assert!(r.is_zero());
+        assert_eq!(quotient, expected);
+
+        assert_eq!(
+            compute_quotient(&base, &ext, &columns, |_, row| row[0] * row[1]).err(),
+            Some(Error::NonZeroRemainder)
+        );
+
+        let mut roots = roots;
+        roots[4] = domain.element(9);
+        let base = Pow2ProductSubtree::construct(&roots).unwrap();
+        assert_eq!(
+            compute_quotient(&base, &ext, &columns, |_, row| row[0]).err(),
+            Some(Error::DomainsNotDisjoint(vec![9]))
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! Interpolation which hands its output to a commitment as it goes, instead of
+//! "interpolate everything, then commit everything".
+//!
+//! Columns are interpolated one after another and the coefficients of each are passed to the
+//! sink in chunks, column by column and in increasing coefficient order within a column.
+//! With the `parallel` feature the sink runs on the calling thread while the next column is
+//! interpolated on rayon's pool, so an MSM over the chunks overlaps with the tree work. The
+//! pipelining is only across columns: a single table is interpolated before any of it is sunk.
+//! Without it, or with a single thread, everything runs on the calling thread in the same order.
+
+use ark_ff::FftField;
+use ark_poly::univariate::DensePolynomial;
+
+use crate::{parallel::pipeline, PolyProcessor};
+
+/// Interpolates every column over the domain of `processor` and calls
+/// `sink(column, offset, coeffs)` with `coeffs[t]` the coefficient of X^(offset + t).
+/// Each column is padded with zeros to the domain size, so it yields ceil(n / chunk_size)
+/// chunks regardless of its degree. The overlap is between columns: the chunks of a column
+/// are only emitted once it is fully interpolated, so a single column gets no overlap.
+/// Blocks the calling thread between columns, so call it from outside rayon's pool, and after
+/// `parallel::init_thread_pool` if a custom pool is used
+pub fn interpolate_into<F, P, S>(processor: &P, columns: &[Vec<F>], chunk_size: usize, mut sink: S)
+where
+    F: FftField,
+    P: PolyProcessor<F> + ?Sized,
+    S: FnMut(usize, usize, &[F]),
+{
+    assert!(chunk_size > 0);
+    let n = processor.size();
+    for column in columns {
+        assert_eq!(column.len(), n);
+    }
+
+    pipeline(
+        columns.len(),
+        |i| padded_coeffs(processor.interpolate(&columns[i]), n),
+        |i, coeffs| emit_chunks(i, &coeffs, chunk_size, &mut sink),
+    );
+}
+
+fn padded_coeffs<F: FftField>(poly: DensePolynomial<F>, n: usize) -> Vec<F> {
+    let mut coeffs = poly.coeffs;
+    coeffs.resize(n, F::zero());
+    coeffs
+}
+
+fn emit_chunks<F: FftField, S: FnMut(usize, usize, &[F])>(
+    column: usize,
+    coeffs: &[F],
+    chunk_size: usize,
+    sink: &mut S,
+) {
+    for (j, chunk) in coeffs.chunks(chunk_size).enumerate() {
+        sink(column, j * chunk_size, chunk);
+    }
+}
+
+#[cfg(test)]
+mod stream_tests {
+    use ark_bn254::Fr;
+    use ark_ff::{UniformRand, Zero};
+    use ark_std::test_rng;
+
+    use crate::{
+        stream::interpolate_into, subtree::Pow2ProductSubtree, PolyProcessor, PolyProcessorStrategy,
+    };
+
+    #[test]
+    fn test_interpolate_into() {
+        let n: usize = 32;
+        let chunk_size = 6;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+
+        // the last column is a constant, its chunks are padded with zeros
+        let mut columns: Vec<Vec<_>> = (0..3)
+            .map(|_| (0..n).map(|_| Fr::rand(&mut rng)).collect())
+            .collect();
+        columns.push(vec![Fr::from(7u64); n]);
+
+        let mut received = vec![];
+        interpolate_into(&subtree, &columns, chunk_size, |column, offset, coeffs| {
+            received.push((column, offset, coeffs.to_vec()))
+        });
+
+        let mut expected = vec![];
+        for (i, column) in columns.iter().enumerate() {
+            let mut coeffs = subtree.interpolate(column).coeffs;
+            coeffs.resize(n, Fr::zero());
+            for (j, chunk) in coeffs.chunks(chunk_size).enumerate() {
+                expected.push((i, j * chunk_size, chunk.to_vec()));
+            }
+        }
+        assert_eq!(received, expected);
+
+        // resolved processors stream as well
+        let processor = PolyProcessorStrategy::resolve(&roots).unwrap();
+        let mut resolved = vec![];
+        interpolate_into(
+            processor.as_ref(),
+            &columns,
+            chunk_size,
+            |column, offset, coeffs| resolved.push((column, offset, coeffs.to_vec())),
+        );
+        assert_eq!(resolved, expected);
+    }
+}
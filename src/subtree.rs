@@ -1,11 +1,15 @@
-use ark_ff::{batch_inversion, FftField};
+use ark_ff::FftField;
 use ark_poly::{
     univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain, Polynomial,
     UVPolynomial,
 };
+use ark_std::cfg_into_iter;
 
 pub use crate::error::Error;
-use crate::{fast_eval::FastEval, PolyProcessor};
+use crate::{fast_eval::FastEval, parallel::join, pointwise, PolyProcessor, WeightMode};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 /// Saves one degree of 2 for FFT when a, b are monic polynomials in leading coefficient
 /// panics if a or b are not monic and degree 2
@@ -41,26 +45,24 @@ pub fn multiply_pow2_monic_polys<F: FftField>(
     let a_evals = domain.fft(a);
     let b_evals = domain.fft(b);
 
-    let product_evals: Vec<F> = a_evals
-        .iter()
-        .zip(b_evals.iter())
-        .map(|(&a, &b)| a * b)
-        .collect();
+    let product_evals = pointwise::mul(&a_evals, &b_evals);
 
     /*
         We know that coefficient of x^(2^m) will be 1 so it will end up in front of x^0,
         That's why we just subtract 1 from free coefficient of resulting poly
     */
-    let mut product_poly = DensePolynomial::from_coefficients_slice(&domain.ifft(&product_evals));
-    product_poly[0] -= F::one();
-    product_poly.coeffs.push(F::one());
+    // coefficients are adjusted before trimming, all of them are zero for X^(2^m) - 1
+    let mut coeffs = domain.ifft(&product_evals);
+    coeffs[0] -= F::one();
+    coeffs.push(F::one());
 
-    product_poly
+    DensePolynomial::from_coefficients_vec(coeffs)
 }
 
 pub struct Pow2ProductSubtree<F: FftField> {
     pub(crate) layers: Vec<Vec<DensePolynomial<F>>>,
-    pub(crate) ri: Vec<F>, // ri = 1/zH'(w^i)
+    pub(crate) ri: Vec<F>,                         // ri = 1/zH'(w^i)
+    pub(crate) vanishing_derivative_evals: Vec<F>, // zH'(w^i)
 }
 
 impl<F: FftField> Pow2ProductSubtree<F> {
@@ -87,36 +89,163 @@ impl<F: FftField> Pow2ProductSubtree<F> {
         let mut nodes_on_layer = n;
         for i in 1..=k {
             nodes_on_layer /= 2;
-            layers[i] = Vec::with_capacity(nodes_on_layer);
-            for j in 0..nodes_on_layer {
-                let lhs_node = layers[i - 1][2 * j].clone();
-                let rhs_node = layers[i - 1][2 * j + 1].clone();
-
-                layers[i].push(multiply_pow2_monic_polys(&lhs_node, &rhs_node));
-            }
+            let prev_layer = &layers[i - 1];
+            layers[i] = cfg_into_iter!(0..nodes_on_layer)
+                .map(|j| {
+                    let lhs_node = &prev_layer[2 * j];
+                    let rhs_node = &prev_layer[2 * j + 1];
+
+                    multiply_pow2_monic_polys(lhs_node, rhs_node)
+                })
+                .collect();
         }
 
         let evals = vec![F::one(); n];
         let vanishing_derivative =
             FastEval::multiply_up_the_tree(&layers, (0, evals.len() - 1), (k, 0), &evals);
 
-        let mut ri = FastEval::divide_down_the_tree(&layers, n, (k, 0), &vanishing_derivative);
-        batch_inversion(&mut ri);
+        let vanishing_derivative_evals =
+            FastEval::divide_down_the_tree(&layers, n, (k, 0), &vanishing_derivative);
+        let mut ri = vanishing_derivative_evals.clone();
+        pointwise::batch_inversion(&mut ri);
 
-        Ok(Self { layers, ri })
+        Ok(Self {
+            layers,
+            ri,
+            vanishing_derivative_evals,
+        })
     }
+
+    /// Evaluations over the domain together with the remainders of f modulo every node of
+    /// `layer` (0 for the leaves X - x_i, log n for zH), in order: node j of the layer covers
+    /// the points x_(j * 2^layer) .. x_((j + 1) * 2^layer - 1)
+    pub fn evaluate_over_domain_with_layer(
+        &self,
+        f: &DensePolynomial<F>,
+        layer: usize,
+    ) -> (Vec<F>, Vec<DensePolynomial<F>>) {
+        let n = self.layers[0].len();
+        let k = self.layers.len() - 1;
+        assert!(f.degree() < n);
+        assert!(layer <= k);
+
+        let remainders = remainders_down_the_tree(&self.layers, (k, 0), layer, f);
+        let evals = remainders
+            .iter()
+            .enumerate()
+            .flat_map(|(j, r)| {
+                FastEval::divide_down_the_tree(&self.layers, 1 << layer, (layer, j), r)
+            })
+            .collect();
+
+        (evals, remainders)
+    }
+
+    /// Variant of `batch_evaluate_lagrange_basis` without the batch inversion.
+    /// Li(point) = ri * prod_{j != i}(point - x_j) is computed from prefix and suffix products,
+    /// so points of the domain need no special casing. This is not constant time: ark-ff
+    /// reduces after every operation with a data-dependent comparison
+    pub fn batch_evaluate_lagrange_basis_no_inversion(&self, point: &F) -> Vec<F> {
+        let n = self.layers[0].len();
+
+        // root monomials are X - x_i, so point - x_i = point + coeffs[0]
+        let diffs: Vec<F> = self.layers[0]
+            .iter()
+            .map(|root_monomial| *point + root_monomial.coeffs[0])
+            .collect();
+
+        // prefix[i] = prod_{j < i}(point - x_j)
+        let mut prefix = Vec::with_capacity(n);
+        let mut acc = F::one();
+        for diff in &diffs {
+            prefix.push(acc);
+            acc *= diff;
+        }
+
+        let mut evals = vec![F::zero(); n];
+        let mut suffix = F::one();
+        for i in (0..n).rev() {
+            evals[i] = self.ri[i] * prefix[i] * suffix;
+            suffix *= diffs[i];
+        }
+
+        evals
+    }
+}
+
+/// Outputs remainders of f modulo the nodes of `layer` below the root node
+fn remainders_down_the_tree<F: FftField>(
+    layers: &Vec<Vec<DensePolynomial<F>>>,
+    root: (usize, usize),
+    layer: usize,
+    f: &DensePolynomial<F>,
+) -> Vec<DensePolynomial<F>> {
+    let (_, r) = FastEval::fast_divide_with_q_and_r(f, &layers[root.0][root.1]).unwrap();
+    if root.0 == layer {
+        return vec![r];
+    }
+
+    let (mut lhs_remainders, rhs_remainders) = join(
+        || remainders_down_the_tree(layers, (root.0 - 1, 2 * root.1), layer, &r),
+        || remainders_down_the_tree(layers, (root.0 - 1, 2 * root.1 + 1), layer, &r),
+    );
+    lhs_remainders.extend(rhs_remainders);
+    lhs_remainders
+}
+
+/// sum of weights[i] * f(x_i) over the points below the root node, the remainders are
+/// reduced as soon as both halves are done so the evaluations are never collected
+fn weighted_sum_down_the_tree<F: FftField>(
+    layers: &Vec<Vec<DensePolynomial<F>>>,
+    root: (usize, usize),
+    f: &DensePolynomial<F>,
+    weights: &[F],
+) -> F {
+    let (_, r) = FastEval::fast_divide_with_q_and_r(f, &layers[root.0][root.1]).unwrap();
+    if root.0 == 0 {
+        // remainder mod X - x_i is the constant f(x_i)
+        return r.coeffs.first().map_or(F::zero(), |&f_i| f_i * weights[0]);
+    }
+
+    let (lhs_weights, rhs_weights) = weights.split_at(weights.len() / 2);
+    let (lhs_sum, rhs_sum) = join(
+        || weighted_sum_down_the_tree(layers, (root.0 - 1, 2 * root.1), &r, lhs_weights),
+        || weighted_sum_down_the_tree(layers, (root.0 - 1, 2 * root.1 + 1), &r, rhs_weights),
+    );
+    lhs_sum + rhs_sum
 }
 
 impl<F: FftField> PolyProcessor<F> for Pow2ProductSubtree<F> {
+    fn size(&self) -> usize {
+        self.layers[0].len()
+    }
+
+    fn elements(&self) -> Vec<F> {
+        // root monomials are X - x_i
+        self.layers[0]
+            .iter()
+            .map(|root_monomial| -root_monomial.coeffs[0])
+            .collect()
+    }
+
     fn get_vanishing(&self) -> DensePolynomial<F> {
         let k = self.layers.len() - 1;
         self.layers[k][0].clone()
     }
 
+    fn evaluate_vanishing_at(&self, point: &F) -> F {
+        let k = self.layers.len() - 1;
+        self.layers[k][0].evaluate(point)
+    }
+
     fn get_ri(&self) -> Vec<F> {
         self.ri.clone()
     }
 
+    fn get_vanishing_derivative_evals(&self) -> Vec<F> {
+        self.vanishing_derivative_evals.clone()
+    }
+
     fn evaluate_over_domain(&self, f: &DensePolynomial<F>) -> Vec<F> {
         let n = self.layers[0].len();
         let k = self.layers.len() - 1;
@@ -125,45 +254,133 @@ impl<F: FftField> PolyProcessor<F> for Pow2ProductSubtree<F> {
         FastEval::divide_down_the_tree(&self.layers, n, (k, 0), f)
     }
 
+    /// Fuses the reduction into the descent, no n-length evaluation vector is allocated
+    fn weighted_sum(&self, f: &DensePolynomial<F>, weights: &[F]) -> F {
+        let n = self.layers[0].len();
+        let k = self.layers.len() - 1;
+
+        assert!(f.degree() < n);
+        assert_eq!(weights.len(), n);
+        weighted_sum_down_the_tree(&self.layers, (k, 0), f, weights)
+    }
+
+    /// Descends f down the tree of (X - x_i)^(k+1), the remainder at leaf i is the Taylor
+    /// expansion of f at x_i up to degree k, from which the derivatives are read
+    fn evaluate_derivatives_over_domain(&self, f: &DensePolynomial<F>, k: usize) -> Vec<Vec<F>> {
+        let n = self.layers[0].len();
+        assert!(f.degree() < n);
+
+        let mut layers = Vec::with_capacity(self.layers.len());
+        layers.push(
+            self.layers[0]
+                .iter()
+                .map(|root_monomial| {
+                    let mut power = root_monomial.clone();
+                    for _ in 0..k {
+                        power = &power * root_monomial;
+                    }
+                    power
+                })
+                .collect::<Vec<_>>(),
+        );
+        for i in 1..self.layers.len() {
+            let layer = layers[i - 1]
+                .chunks(2)
+                .map(|pair| &pair[0] * &pair[1])
+                .collect();
+            layers.push(layer);
+        }
+
+        let remainders = remainders_down_the_tree(&layers, (layers.len() - 1, 0), 0, f);
+
+        let mut derivatives_evals = vec![Vec::with_capacity(n); k + 1];
+        for (r, root_monomial) in remainders.iter().zip(self.layers[0].iter()) {
+            // Taylor shift: coefficients of r(X + x_i)
+            let x_i = -root_monomial.coeffs[0];
+            let mut taylor = r.coeffs.clone();
+            taylor.resize(k + 1, F::zero());
+            for j in 0..k {
+                for t in (j..k).rev() {
+                    let shifted = x_i * taylor[t + 1];
+                    taylor[t] += shifted;
+                }
+            }
+
+            // f^(j)(x_i) = j! * taylor[j]
+            let mut factorial = F::one();
+            for (j, coeff) in taylor.iter().enumerate() {
+                if j > 0 {
+                    factorial *= F::from(j as u64);
+                }
+                derivatives_evals[j].push(factorial * coeff);
+            }
+        }
+
+        derivatives_evals
+    }
+
     fn interpolate(&self, evals: &[F]) -> DensePolynomial<F> {
         assert_eq!(evals.len(), self.ri.len());
         let k = self.layers.len() - 1;
-        let evals = evals
-            .iter()
-            .zip(self.ri.iter())
-            .map(|(&vi, &ri)| vi * ri)
-            .collect::<Vec<_>>();
+        let evals = pointwise::mul(evals, &self.ri);
+        FastEval::multiply_up_the_tree(&self.layers, (0, evals.len() - 1), (k, 0), &evals)
+    }
+
+    /// Weights are folded into the leaves of the product tree, so there is no extra pass
+    fn interpolate_weighted(
+        &self,
+        evals: &[F],
+        weights: &[F],
+        mode: WeightMode,
+    ) -> DensePolynomial<F> {
+        assert_eq!(evals.len(), self.ri.len());
+        assert_eq!(weights.len(), self.ri.len());
+        let k = self.layers.len() - 1;
+        let evals: Vec<_> = match mode {
+            WeightMode::Compose => evals
+                .iter()
+                .zip(weights.iter())
+                .zip(self.ri.iter())
+                .map(|((&f_i, &w_i), &ri)| f_i * w_i * ri)
+                .collect(),
+            WeightMode::Replace => evals
+                .iter()
+                .zip(weights.iter())
+                .map(|(&f_i, &w_i)| f_i * w_i)
+                .collect(),
+        };
         FastEval::multiply_up_the_tree(&self.layers, (0, evals.len() - 1), (k, 0), &evals)
     }
 
     fn batch_evaluate_lagrange_basis(&self, point: &F) -> Vec<F> {
-        let mut monomials_evals = Vec::with_capacity(self.layers[0].len());
-        for root_monomial in &self.layers[0] {
-            monomials_evals.push(root_monomial.evaluate(point));
-        }
-        batch_inversion(&mut monomials_evals);
+        let mut monomials_evals = pointwise::evaluate_monomials(&self.elements(), point);
+        pointwise::batch_inversion(&mut monomials_evals);
 
-        let k = self.layers.len() - 1;
-        let vh_eval = self.layers[k][0].evaluate(point);
+        let vh_eval = self.evaluate_vanishing_at(point);
 
-        self.ri
-            .iter()
-            .zip(monomials_evals.iter())
-            .map(|(&ri, monomial_i)| ri * monomial_i * vh_eval)
-            .collect()
+        let weights = pointwise::mul(&self.ri, &monomials_evals);
+        pointwise::scale(&weights, &vh_eval)
     }
 }
 
 #[cfg(test)]
 mod subtree_tests {
     use ark_bn254::Fr;
-    use ark_ff::{FftField, One, UniformRand};
-    use ark_poly::{univariate::DensePolynomial, Polynomial, UVPolynomial};
+    use ark_ff::{FftField, One, UniformRand, Zero};
+    use ark_poly::{
+        univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain, Polynomial,
+        UVPolynomial,
+    };
     use ark_std::test_rng;
 
     use crate::{
+        error::Error,
+        fast_eval::FastEval,
+        fft::FftProcessor,
+        gcd::RootsCertificate,
         subtree::{multiply_pow2_monic_polys, Pow2ProductSubtree},
-        PolyProcessor,
+        test_fields::BabyBear,
+        PolyProcessor, WeightMode,
     };
 
     /// given x coords construct Li polynomials
@@ -203,6 +420,20 @@ mod subtree_tests {
         let product_slow = &a * &b;
         let product_fast = multiply_pow2_monic_polys(&a, &b);
         assert_eq!(product_fast, product_slow);
+
+        // (X - c)(X + c) = X^2 - c^2 has a zero coefficient in the middle
+        let c = Fr::rand(&mut rng);
+        let a = DensePolynomial::from_coefficients_slice(&[-c, Fr::one()]);
+        let b = DensePolynomial::from_coefficients_slice(&[c, Fr::one()]);
+        assert_eq!(multiply_pow2_monic_polys(&a, &b), &a * &b);
+
+        // (X^n - 1)(X^n + 1) = X^2n - 1 is all zeros mod X^2n - 1
+        let mut a_coeffs = vec![Fr::zero(); n + 1];
+        a_coeffs[0] = -Fr::one();
+        a_coeffs[n] = Fr::one();
+        let a = DensePolynomial::from_coefficients_vec(a_coeffs);
+        let b = &a + &DensePolynomial::from_coefficients_slice(&[Fr::from(2u64)]);
+        assert_eq!(multiply_pow2_monic_polys(&a, &b), &a * &b);
     }
 
     #[test]
@@ -253,6 +484,21 @@ mod subtree_tests {
         assert_eq!(f_slow, f_fast);
     }
 
+    #[test]
+    fn test_evaluate_over_domain_vanishing_at_roots() {
+        let n: usize = 32;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+
+        // L3 vanishes on every root but x_3, so most remainders at the leaves are zero
+        let lagrange_basis = construct_lagrange_basis(&roots);
+        let mut expected = vec![Fr::zero(); n];
+        expected[3] = Fr::one();
+        assert_eq!(subtree.evaluate_over_domain(&lagrange_basis[3]), expected);
+    }
+
     #[test]
     fn test_evaluate_over_domain() {
         let n: usize = 32;
@@ -272,4 +518,444 @@ mod subtree_tests {
         let f_computed_evals = subtree.evaluate_over_domain(&f);
         assert_eq!(f_evals, f_computed_evals);
     }
+
+    #[test]
+    fn test_evaluate_over_domain_ext() {
+        let n: usize = 32;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+
+        let f = DensePolynomial::<Fr>::rand(n - 1, &mut rng);
+        let f_evals: Vec<_> = roots.iter().map(|x_i| f.evaluate(x_i)).collect();
+
+        let alpha = Fr::rand(&mut rng);
+        let f_ext_evals = subtree.evaluate_over_domain_ext(&f, &[alpha, roots[2]]);
+        assert_eq!(f_ext_evals[..n], f_evals[..]);
+        assert_eq!(f_ext_evals[n..], [f.evaluate(&alpha), f_evals[2]]);
+    }
+
+    #[test]
+    fn test_weighted_sum() {
+        let n: usize = 32;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+
+        let f = DensePolynomial::<Fr>::rand(n - 1, &mut rng);
+        let weights: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let expected: Fr = roots
+            .iter()
+            .zip(weights.iter())
+            .map(|(x_i, w_i)| f.evaluate(x_i) * w_i)
+            .sum();
+        assert_eq!(subtree.weighted_sum(&f, &weights), expected);
+
+        let domain = GeneralEvaluationDomain::<Fr>::new(n).unwrap();
+        let fft_processor = FftProcessor::construct(domain).unwrap();
+        let expected: Fr = domain
+            .elements()
+            .zip(weights.iter())
+            .map(|(x_i, w_i)| f.evaluate(&x_i) * w_i)
+            .sum();
+        assert_eq!(fft_processor.weighted_sum(&f, &weights), expected);
+    }
+
+    #[test]
+    fn test_interpolate_with_degree_bound() {
+        let n: usize = 32;
+        let d: usize = 10;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+
+        let f = DensePolynomial::<Fr>::rand(d - 1, &mut rng);
+        let mut f_evals = subtree.evaluate_over_domain(&f);
+        assert_eq!(
+            subtree.interpolate_with_degree_bound(&f_evals, d).unwrap(),
+            f
+        );
+        assert_eq!(
+            subtree.interpolate_with_degree_bound(&f_evals, d - 1).err(),
+            Some(Error::DegreeBoundExceeded(d - 1))
+        );
+
+        // malformed witness: one wrong evaluation spreads over the whole window
+        f_evals[5] += Fr::one();
+        assert_eq!(
+            subtree.interpolate_with_degree_bound(&f_evals, d).err(),
+            Some(Error::DegreeBoundExceeded(n - 1))
+        );
+        assert!(subtree.interpolate_with_degree_bound(&f_evals, n).is_ok());
+    }
+
+    #[test]
+    fn test_evaluate_over_domain_with_layer() {
+        let n: usize = 32;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+
+        let f = DensePolynomial::<Fr>::rand(n - 1, &mut rng);
+        let f_evals = subtree.evaluate_over_domain(&f);
+        for layer in 0..=5 {
+            let (evals, remainders) = subtree.evaluate_over_domain_with_layer(&f, layer);
+            assert_eq!(evals, f_evals);
+            assert_eq!(remainders.len(), n >> layer);
+            for (r, node) in remainders.iter().zip(subtree.layers[layer].iter()) {
+                let (_, expected) = FastEval::fast_divide_with_q_and_r(&f, node).unwrap();
+                assert_eq!(*r, expected);
+            }
+        }
+
+        let (_, remainders) = subtree.evaluate_over_domain_with_layer(&f, 5);
+        assert_eq!(remainders, vec![f]);
+    }
+
+    #[test]
+    fn test_interpolate_weighted() {
+        let n: usize = 32;
+        let mut rng = test_rng();
+
+        let domain = GeneralEvaluationDomain::<Fr>::new(n).unwrap();
+        let roots: Vec<_> = domain.elements().collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+        let fft_processor = FftProcessor::construct(domain).unwrap();
+
+        let f_evals: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let weights: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let ones = vec![Fr::one(); n];
+        let weighted_evals: Vec<_> = f_evals
+            .iter()
+            .zip(weights.iter())
+            .map(|(f_i, w_i)| *f_i * w_i)
+            .collect();
+
+        for processor in [&subtree as &dyn PolyProcessor<Fr>, &fft_processor] {
+            let f = processor.interpolate(&f_evals);
+            assert_eq!(
+                processor.interpolate_weighted(&f_evals, &ones, WeightMode::Compose),
+                f
+            );
+            assert_eq!(
+                processor.interpolate_weighted(&f_evals, &processor.get_ri(), WeightMode::Replace),
+                f
+            );
+            assert_eq!(
+                processor.interpolate_weighted(&f_evals, &weights, WeightMode::Compose),
+                processor.interpolate(&weighted_evals)
+            );
+
+            // sum_i w_i * f_i * zH(X)/(X - x_i) at a domain point x_j is w_j * f_j * zH'(x_j)
+            let g = processor.interpolate_weighted(&f_evals, &weights, WeightMode::Replace);
+            let zh_prime_evals = processor.get_vanishing_derivative_evals();
+            assert_eq!(g.evaluate(&roots[5]), weighted_evals[5] * zh_prime_evals[5]);
+        }
+    }
+
+    #[test]
+    fn test_all_lagrange_coefficients() {
+        let n: usize = 16;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+        assert_eq!(
+            subtree.all_lagrange_coefficients(),
+            construct_lagrange_basis(&roots)
+        );
+
+        let domain = GeneralEvaluationDomain::<Fr>::new(n).unwrap();
+        let fft_processor = FftProcessor::construct(domain).unwrap();
+        let basis = fft_processor.all_lagrange_coefficients();
+        for (i, li) in basis.iter().enumerate() {
+            let expected = domain.evaluate_all_lagrange_coefficients(roots[0])[i];
+            assert_eq!(li.evaluate(&roots[0]), expected);
+            assert_eq!(li.evaluate(&domain.element(i)), Fr::one());
+        }
+    }
+
+    #[test]
+    fn test_vanishing_quotients() {
+        let n: usize = 16;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+        let vanishing = subtree.get_vanishing();
+
+        let quotients = subtree.vanishing_quotients();
+        for (quotient, root) in quotients.iter().zip(roots.iter()) {
+            let monomial = DensePolynomial::from_coefficients_slice(&[-*root, Fr::one()]);
+            assert_eq!(&monomial * quotient, vanishing);
+        }
+
+        // Y shares y_1 = x_4 and y_6 = x_0 with H
+        let mut ys: Vec<_> = (0..2 * n).map(|_| Fr::rand(&mut rng)).collect();
+        ys[1] = roots[4];
+        ys[6] = roots[0];
+        let y_processor = Pow2ProductSubtree::construct(&ys).unwrap();
+        let quotients_evals = subtree.vanishing_quotients_over(&y_processor);
+        for (quotient, row) in quotients.iter().zip(quotients_evals.iter()) {
+            let expected: Vec<_> = ys.iter().map(|y_j| quotient.evaluate(y_j)).collect();
+            assert_eq!(*row, expected);
+        }
+
+        let domain = GeneralEvaluationDomain::<Fr>::new(2 * n).unwrap();
+        let fft_processor = FftProcessor::construct(domain).unwrap();
+        let quotients_evals = subtree.vanishing_quotients_over(&fft_processor);
+        for (quotient, row) in quotients.iter().zip(quotients_evals.iter()) {
+            assert_eq!(*row, fft_processor.evaluate_over_domain(quotient));
+        }
+    }
+
+    #[test]
+    fn test_multiply_evals() {
+        let n: usize = 32;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+
+        let a = DensePolynomial::<Fr>::rand(n - 1, &mut rng);
+        let b = DensePolynomial::<Fr>::rand(n - 1, &mut rng);
+        let a_evals = subtree.evaluate_over_domain(&a);
+        let b_evals = subtree.evaluate_over_domain(&b);
+        let product = &a * &b;
+
+        let domain = GeneralEvaluationDomain::<Fr>::new(2 * n).unwrap();
+        let product_evals = subtree.multiply_evals(&a_evals, &b_evals, None).unwrap();
+        assert_eq!(product_evals, domain.fft(&product));
+
+        let extension_roots: Vec<_> = (0..2 * n).map(|_| Fr::rand(&mut rng)).collect();
+        let extension = Pow2ProductSubtree::construct(&extension_roots).unwrap();
+        let product_evals = subtree
+            .multiply_evals(&a_evals, &b_evals, Some(&extension))
+            .unwrap();
+        let expected: Vec<_> = extension_roots
+            .iter()
+            .map(|root| product.evaluate(root))
+            .collect();
+        assert_eq!(product_evals, expected);
+
+        let res = subtree.multiply_evals(&a_evals, &b_evals, Some(&subtree));
+        assert_eq!(res.err(), Some(Error::ExtensionTooSmall));
+    }
+
+    #[test]
+    fn test_divide_evals() {
+        let n: usize = 32;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+
+        let q = DensePolynomial::<Fr>::rand(n / 2 - 1, &mut rng);
+        let den = DensePolynomial::<Fr>::rand(n / 2 - 1, &mut rng);
+        let num = &q * &den;
+
+        let num_evals = subtree.evaluate_over_domain(&num);
+        let mut den_evals = subtree.evaluate_over_domain(&den);
+        let q_evals = subtree.evaluate_over_domain(&q);
+
+        assert_eq!(
+            subtree.divide_evals(&num_evals, &den_evals).unwrap(),
+            q_evals
+        );
+        assert_eq!(
+            subtree.divide_evals_exact(&num_evals, &den_evals).unwrap(),
+            q_evals
+        );
+
+        let other_evals = subtree.evaluate_over_domain(&DensePolynomial::rand(n - 1, &mut rng));
+        assert!(subtree.divide_evals(&other_evals, &den_evals).is_ok());
+        assert_eq!(
+            subtree.divide_evals_exact(&other_evals, &den_evals).err(),
+            Some(Error::NonZeroRemainder)
+        );
+
+        den_evals[3] = Fr::zero();
+        den_evals[7] = Fr::zero();
+        assert_eq!(
+            subtree.divide_evals(&num_evals, &den_evals).err(),
+            Some(Error::ZeroDenominator(vec![3, 7]))
+        );
+    }
+
+    #[test]
+    fn test_lagrange_basis_no_inversion() {
+        let n: usize = 32;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+
+        let alpha = Fr::rand(&mut rng);
+        assert_eq!(
+            subtree.batch_evaluate_lagrange_basis_no_inversion(&alpha),
+            subtree.batch_evaluate_lagrange_basis(&alpha)
+        );
+
+        let li_evals = subtree.batch_evaluate_lagrange_basis_no_inversion(&roots[5]);
+        for (i, li_eval) in li_evals.iter().enumerate() {
+            if i == 5 {
+                assert_eq!(*li_eval, Fr::one());
+            } else {
+                assert_eq!(*li_eval, Fr::zero());
+            }
+        }
+    }
+
+    #[test]
+    fn test_divide_by_subset_vanishing() {
+        let n: usize = 32;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+
+        let subset = vec![1, 4, 9, 16, 25];
+        let mut zs = DensePolynomial::from_coefficients_slice(&[Fr::one()]);
+        for &i in &subset {
+            zs = &zs * &DensePolynomial::from_coefficients_slice(&[-roots[i], Fr::one()]);
+        }
+
+        let q = DensePolynomial::<Fr>::rand(n - subset.len() - 1, &mut rng);
+        let f = &q * &zs;
+        let f_evals = subtree.evaluate_over_domain(&f);
+
+        let q_evals = subtree
+            .divide_by_subset_vanishing(&f_evals, &subset)
+            .unwrap();
+        assert_eq!(q_evals, subtree.evaluate_over_domain(&q));
+
+        let mut g_evals = f_evals.clone();
+        g_evals[4] = Fr::one();
+        g_evals[16] = Fr::one();
+        assert_eq!(
+            subtree.divide_by_subset_vanishing(&g_evals, &subset).err(),
+            Some(Error::NotVanishingOnSubset(vec![4, 16]))
+        );
+
+        assert_eq!(
+            subtree.divide_by_subset_vanishing(&f_evals, &[]).unwrap(),
+            f_evals
+        );
+    }
+
+    #[test]
+    fn test_vanishing_derivative_evals() {
+        let n: usize = 32;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+
+        let mut vanishing = DensePolynomial::from_coefficients_slice(&[Fr::one()]);
+        for root in &roots {
+            vanishing =
+                &vanishing * &DensePolynomial::from_coefficients_slice(&[-*root, Fr::one()]);
+        }
+        let vanishing_derivative = FastEval::derivative(&vanishing);
+
+        let expected: Vec<_> = roots
+            .iter()
+            .map(|root| vanishing_derivative.evaluate(root))
+            .collect();
+        let vanishing_derivative_evals = subtree.get_vanishing_derivative_evals();
+        assert_eq!(vanishing_derivative_evals, expected);
+
+        for (ri, zh_prime_i) in subtree
+            .get_ri()
+            .iter()
+            .zip(vanishing_derivative_evals.iter())
+        {
+            assert_eq!(*ri * zh_prime_i, Fr::one());
+        }
+    }
+
+    #[test]
+    fn test_evaluate_vanishing_at() {
+        let n: usize = 32;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+        let vanishing = subtree.get_vanishing();
+
+        let points = vec![Fr::rand(&mut rng), roots[7], Fr::rand(&mut rng)];
+        let expected: Vec<_> = points.iter().map(|p| vanishing.evaluate(p)).collect();
+        assert_eq!(subtree.evaluate_vanishing_at(&points[0]), expected[0]);
+        assert_eq!(subtree.evaluate_vanishing_at_many(&points), expected);
+        assert!(expected[1].is_zero());
+    }
+
+    #[test]
+    fn test_evaluate_derivatives_over_domain() {
+        let n: usize = 32;
+        let k: usize = 3;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+
+        let f = DensePolynomial::<Fr>::rand(n - 1, &mut rng);
+        let derivatives_evals = subtree.evaluate_derivatives_over_domain(&f, k);
+        assert_eq!(derivatives_evals.len(), k + 1);
+
+        let mut derivative = f;
+        for evals in derivatives_evals {
+            let expected: Vec<_> = roots.iter().map(|root| derivative.evaluate(root)).collect();
+            assert_eq!(evals, expected);
+            derivative = FastEval::derivative(&derivative);
+        }
+    }
+
+    #[test]
+    fn test_certify_distinct_roots() {
+        let n: usize = 32;
+        let mut rng = test_rng();
+
+        let mut roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+
+        let certificate = subtree.certify_distinct_roots();
+        assert!(matches!(certificate, RootsCertificate::Distinct { .. }));
+        assert!(certificate.verify(&subtree.get_vanishing()));
+
+        roots[7] = roots[20];
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+        assert_eq!(
+            subtree.certify_distinct_roots(),
+            RootsCertificate::Repeated(DensePolynomial::from_coefficients_slice(&[
+                -roots[20],
+                Fr::one()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_word_field_lagrange_basis() {
+        let n: usize = 32;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| BabyBear::rand(&mut rng)).collect();
+        let subtree = Pow2ProductSubtree::construct(&roots).unwrap();
+
+        let f = DensePolynomial::<BabyBear>::rand(n - 1, &mut rng);
+        let f_evals = subtree.evaluate_over_domain(&f);
+        assert_eq!(subtree.interpolate(&f_evals), f);
+
+        let alpha = BabyBear::rand(&mut rng);
+        let f_alpha = subtree
+            .batch_evaluate_lagrange_basis(&alpha)
+            .iter()
+            .zip(f_evals.iter())
+            .fold(BabyBear::zero(), |acc, (li, fi)| acc + *li * fi);
+        assert_eq!(f_alpha, f.evaluate(&alpha));
+    }
 }
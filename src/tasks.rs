@@ -0,0 +1,349 @@
+//! Serializable descriptions of the heavy tree operations, so they can be delegated to remote
+//! workers while the local process keeps only the domain points and the barycentric weights.
+//!
+//! A `Task` names the domain and the input by their digests, the input itself is shipped
+//! alongside it. Results are never trusted: before being applied they are checked at a random
+//! point through the barycentric form f(α) = zH(α) * sum_i ri * f(x_i) / (α - x_i), which costs
+//! O(n) with a single inversion instead of rebuilding the O(n log^2 n) tree.
+
+use ark_ff::{batch_inversion, FftField};
+use ark_poly::{univariate::DensePolynomial, Polynomial, UVPolynomial};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use ark_std::rand::Rng;
+use digest::Digest;
+
+use crate::{error::Error, PolyProcessor};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    /// input: the roots, output: the weights ri
+    Construct,
+    /// input: coefficients of f, output: evaluations of f over the domain
+    Evaluate,
+    /// input: evaluations over the domain, output: coefficients of the interpolant
+    Interpolate,
+}
+
+impl CanonicalSerialize for Operation {
+    fn serialize<W: Write>(&self, writer: W) -> Result<(), SerializationError> {
+        let tag: u8 = match self {
+            Self::Construct => 0,
+            Self::Evaluate => 1,
+            Self::Interpolate => 2,
+        };
+        tag.serialize(writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        1
+    }
+}
+
+impl CanonicalDeserialize for Operation {
+    fn deserialize<R: Read>(reader: R) -> Result<Self, SerializationError> {
+        match u8::deserialize(reader)? {
+            0 => Ok(Self::Construct),
+            1 => Ok(Self::Evaluate),
+            2 => Ok(Self::Interpolate),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Task {
+    pub operation: Operation,
+    /// digest of the domain points
+    pub domain_fingerprint: Vec<u8>,
+    pub input_digest: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct TaskResult<F: FftField> {
+    pub task: Task,
+    pub output: Vec<F>,
+}
+
+fn digest_of<D: Digest, F: FftField>(elements: &[F]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(elements.serialized_size());
+    elements.serialize(&mut bytes).unwrap();
+    D::digest(&bytes).to_vec()
+}
+
+impl Task {
+    pub fn construct<D: Digest, F: FftField>(roots: &[F]) -> Self {
+        let digest = digest_of::<D, F>(roots);
+        Self {
+            operation: Operation::Construct,
+            domain_fingerprint: digest.clone(),
+            input_digest: digest,
+        }
+    }
+
+    pub fn evaluate<D: Digest, F: FftField>(
+        domain: &DomainState<F>,
+        f: &DensePolynomial<F>,
+    ) -> Self {
+        Self {
+            operation: Operation::Evaluate,
+            domain_fingerprint: domain.fingerprint.clone(),
+            input_digest: digest_of::<D, F>(&f.coeffs),
+        }
+    }
+
+    pub fn interpolate<D: Digest, F: FftField>(domain: &DomainState<F>, evals: &[F]) -> Self {
+        Self {
+            operation: Operation::Interpolate,
+            domain_fingerprint: domain.fingerprint.clone(),
+            input_digest: digest_of::<D, F>(evals),
+        }
+    }
+}
+
+/// Worker side: runs the task with the worker's processor for the domain (for `Construct`,
+/// the processor built from the shipped roots).
+/// Fails with `TaskMismatch` if the processor or the input don't match the digests
+pub fn execute<D: Digest, F: FftField>(
+    task: &Task,
+    processor: &dyn PolyProcessor<F>,
+    input: &[F],
+) -> Result<TaskResult<F>, Error> {
+    if digest_of::<D, F>(&processor.elements()) != task.domain_fingerprint
+        || digest_of::<D, F>(input) != task.input_digest
+    {
+        return Err(Error::TaskMismatch);
+    }
+
+    let output = match task.operation {
+        Operation::Construct => processor.get_ri(),
+        Operation::Evaluate => {
+            processor.evaluate_over_domain(&DensePolynomial::from_coefficients_slice(input))
+        }
+        Operation::Interpolate => processor.interpolate(input).coeffs,
+    };
+
+    Ok(TaskResult {
+        task: task.clone(),
+        output,
+    })
+}
+
+/// Thin local state of a domain: its points and the barycentric weights ri = 1/zH'(x_i)
+pub struct DomainState<F: FftField> {
+    elements: Vec<F>,
+    ri: Vec<F>,
+    fingerprint: Vec<u8>,
+}
+
+impl<F: FftField> DomainState<F> {
+    pub fn from_processor<D: Digest>(processor: &dyn PolyProcessor<F>) -> Self {
+        let elements = processor.elements();
+        Self {
+            fingerprint: digest_of::<D, F>(&elements),
+            elements,
+            ri: processor.get_ri(),
+        }
+    }
+
+    /// Applies the result of a `Construct` task for the roots.
+    /// The weights are checked with the partial fractions of 1/zH:
+    /// sum_i ri / (α - x_i) = 1 / zH(α)
+    pub fn from_construct_result<D: Digest, R: Rng>(
+        roots: &[F],
+        result: &TaskResult<F>,
+        rng: &mut R,
+    ) -> Result<Self, Error> {
+        let fingerprint = digest_of::<D, F>(roots);
+        if result.task != Task::construct::<D, F>(roots) || result.output.len() != roots.len() {
+            return Err(Error::TaskMismatch);
+        }
+
+        let state = Self {
+            elements: roots.to_vec(),
+            ri: result.output.clone(),
+            fingerprint,
+        };
+        let (_, zh_alpha, diffs_inv) = state.random_point_diffs(rng);
+        let sum = state
+            .ri
+            .iter()
+            .zip(diffs_inv.iter())
+            .fold(F::zero(), |acc, (ri, diff_inv)| acc + *ri * diff_inv);
+        if sum * zh_alpha != F::one() {
+            return Err(Error::VerificationFailed);
+        }
+
+        Ok(state)
+    }
+
+    /// Applies the result of an `Evaluate` or `Interpolate` task for the given input,
+    /// the evaluations are checked against the coefficients at a random point
+    pub fn apply<D: Digest, R: Rng>(
+        &self,
+        input: &[F],
+        result: &TaskResult<F>,
+        rng: &mut R,
+    ) -> Result<Vec<F>, Error> {
+        let n = self.elements.len();
+        let task = &result.task;
+        if task.domain_fingerprint != self.fingerprint
+            || task.input_digest != digest_of::<D, F>(input)
+        {
+            return Err(Error::TaskMismatch);
+        }
+
+        let (coeffs, evals) = match task.operation {
+            Operation::Evaluate if result.output.len() == n && input.len() <= n => {
+                (input, &result.output[..])
+            }
+            Operation::Interpolate if input.len() == n && result.output.len() <= n => {
+                (&result.output[..], input)
+            }
+            _ => return Err(Error::TaskMismatch),
+        };
+
+        let (alpha, zh_alpha, diffs_inv) = self.random_point_diffs(rng);
+        let barycentric = evals
+            .iter()
+            .zip(self.ri.iter())
+            .zip(diffs_inv.iter())
+            .fold(F::zero(), |acc, ((eval, ri), diff_inv)| {
+                acc + *eval * ri * diff_inv
+            });
+        let f = DensePolynomial::from_coefficients_slice(coeffs);
+        if f.evaluate(&alpha) != zh_alpha * barycentric {
+            return Err(Error::VerificationFailed);
+        }
+
+        Ok(result.output.clone())
+    }
+
+    /// Samples α outside of the domain, outputs α, zH(α) and 1/(α - x_i)
+    fn random_point_diffs<R: Rng>(&self, rng: &mut R) -> (F, F, Vec<F>) {
+        loop {
+            let alpha = F::rand(rng);
+            let mut diffs: Vec<_> = self.elements.iter().map(|x_i| alpha - x_i).collect();
+            if diffs.iter().any(|diff| diff.is_zero()) {
+                continue;
+            }
+            let zh_alpha = diffs.iter().product();
+            batch_inversion(&mut diffs);
+            return (alpha, zh_alpha, diffs);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tasks_tests {
+    use std::{collections::hash_map::DefaultHasher, hash::Hasher};
+
+    use ark_bn254::Fr;
+    use ark_ff::UniformRand;
+    use ark_poly::{univariate::DensePolynomial, UVPolynomial};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_std::test_rng;
+    #[allow(deprecated)]
+    use digest::{consts::U8, generic_array::GenericArray, FixedOutput, Reset, Update};
+
+    use crate::{
+        error::Error,
+        subtree::Pow2ProductSubtree,
+        tasks::{execute, DomainState, Task, TaskResult},
+    };
+
+    /// Stand-in digest for the tests, real deployments plug in sha2, blake2, ...
+    #[derive(Clone, Default)]
+    struct SipDigest(DefaultHasher);
+
+    impl Update for SipDigest {
+        fn update(&mut self, data: impl AsRef<[u8]>) {
+            self.0.write(data.as_ref());
+        }
+    }
+
+    // digest 0.9 pins the deprecated generic-array 0.14
+    #[allow(deprecated)]
+    impl FixedOutput for SipDigest {
+        type OutputSize = U8;
+
+        fn finalize_into(self, out: &mut GenericArray<u8, U8>) {
+            out.copy_from_slice(&self.0.finish().to_le_bytes());
+        }
+
+        fn finalize_into_reset(&mut self, out: &mut GenericArray<u8, U8>) {
+            out.copy_from_slice(&self.0.finish().to_le_bytes());
+            self.reset();
+        }
+    }
+
+    impl Reset for SipDigest {
+        fn reset(&mut self) {
+            self.0 = DefaultHasher::default();
+        }
+    }
+
+    /// serializes and deserializes, as if sent over the wire
+    fn roundtrip<T: CanonicalSerialize + CanonicalDeserialize>(value: &T) -> T {
+        let mut bytes = vec![];
+        value.serialize(&mut bytes).unwrap();
+        T::deserialize(&bytes[..]).unwrap()
+    }
+
+    #[test]
+    fn test_delegated_tasks() {
+        let n: usize = 32;
+        let mut rng = test_rng();
+
+        let roots: Vec<_> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+
+        // worker builds the tree, local side keeps the thin state
+        let task = roundtrip(&Task::construct::<SipDigest, _>(&roots));
+        let worker = Pow2ProductSubtree::construct(&roots).unwrap();
+        let result = roundtrip(&execute::<SipDigest, _>(&task, &worker, &roots).unwrap());
+        let domain =
+            DomainState::from_construct_result::<SipDigest, _>(&roots, &result, &mut rng).unwrap();
+
+        let mut bad_result = result.clone();
+        bad_result.output[3] += Fr::from(1u64);
+        assert_eq!(
+            DomainState::from_construct_result::<SipDigest, _>(&roots, &bad_result, &mut rng).err(),
+            Some(Error::VerificationFailed)
+        );
+
+        let f = DensePolynomial::<Fr>::rand(n - 1, &mut rng);
+        let task = roundtrip(&Task::evaluate::<SipDigest, _>(&domain, &f));
+        let result: TaskResult<Fr> =
+            roundtrip(&execute::<SipDigest, _>(&task, &worker, &f.coeffs).unwrap());
+        let evals = domain
+            .apply::<SipDigest, _>(&f.coeffs, &result, &mut rng)
+            .unwrap();
+
+        let task = Task::interpolate::<SipDigest, _>(&domain, &evals);
+        let result = execute::<SipDigest, _>(&task, &worker, &evals).unwrap();
+        let coeffs = domain
+            .apply::<SipDigest, _>(&evals, &result, &mut rng)
+            .unwrap();
+        assert_eq!(DensePolynomial::from_coefficients_vec(coeffs), f);
+
+        let mut bad_result = result.clone();
+        bad_result.output[0] += Fr::from(1u64);
+        assert_eq!(
+            domain
+                .apply::<SipDigest, _>(&evals, &bad_result, &mut rng)
+                .err(),
+            Some(Error::VerificationFailed)
+        );
+
+        // result for other inputs
+        assert_eq!(
+            domain
+                .apply::<SipDigest, _>(&f.coeffs, &result, &mut rng)
+                .err(),
+            Some(Error::TaskMismatch)
+        );
+        assert_eq!(
+            execute::<SipDigest, _>(&task, &worker, &f.coeffs).err(),
+            Some(Error::TaskMismatch)
+        );
+    }
+}
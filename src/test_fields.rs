@@ -0,0 +1,84 @@
+//! Small prime fields shared by the tests and benches: a word-sized field and one small enough
+//! to run out of subgroups and cosets.
+
+use ark_ff::{biginteger::BigInteger64, fields::Fp64, FftParameters, Fp64Parameters, FpParameters};
+
+/// BabyBear, p = 15 * 2^27 + 1
+pub type BabyBear = Fp64<BabyBearParameters>;
+
+pub struct BabyBearParameters;
+
+impl Fp64Parameters for BabyBearParameters {}
+
+impl FftParameters for BabyBearParameters {
+    type BigInt = BigInteger64;
+
+    const TWO_ADICITY: u32 = 27;
+
+    const TWO_ADIC_ROOT_OF_UNITY: BigInteger64 = BigInteger64([743410387]);
+}
+
+impl FpParameters for BabyBearParameters {
+    const MODULUS: BigInteger64 = BigInteger64([2013265921]);
+
+    const MODULUS_BITS: u32 = 31;
+
+    const CAPACITY: u32 = Self::MODULUS_BITS - 1;
+
+    const REPR_SHAVE_BITS: u32 = 33;
+
+    const R: BigInteger64 = BigInteger64([1172168163]);
+
+    const R2: BigInteger64 = BigInteger64([663890614]);
+
+    const INV: u64 = 14393504411089371135;
+
+    // 31
+    const GENERATOR: BigInteger64 = BigInteger64([98426475]);
+
+    const MODULUS_MINUS_ONE_DIV_TWO: BigInteger64 = BigInteger64([1006632960]);
+
+    const T: BigInteger64 = BigInteger64([15]);
+
+    const T_MINUS_ONE_DIV_TWO: BigInteger64 = BigInteger64([7]);
+}
+
+/// p = 3 * 2^5 + 1, small enough to run out of subgroups in tests
+pub type F97 = Fp64<F97Parameters>;
+
+pub struct F97Parameters;
+
+impl Fp64Parameters for F97Parameters {}
+
+impl FftParameters for F97Parameters {
+    type BigInt = BigInteger64;
+
+    const TWO_ADICITY: u32 = 5;
+
+    const TWO_ADIC_ROOT_OF_UNITY: BigInteger64 = BigInteger64([59]);
+}
+
+impl FpParameters for F97Parameters {
+    const MODULUS: BigInteger64 = BigInteger64([97]);
+
+    const MODULUS_BITS: u32 = 7;
+
+    const CAPACITY: u32 = Self::MODULUS_BITS - 1;
+
+    const REPR_SHAVE_BITS: u32 = 57;
+
+    const R: BigInteger64 = BigInteger64([61]);
+
+    const R2: BigInteger64 = BigInteger64([35]);
+
+    const INV: u64 = 6656041676080766047;
+
+    // 5
+    const GENERATOR: BigInteger64 = BigInteger64([14]);
+
+    const MODULUS_MINUS_ONE_DIV_TWO: BigInteger64 = BigInteger64([48]);
+
+    const T: BigInteger64 = BigInteger64([3]);
+
+    const T_MINUS_ONE_DIV_TWO: BigInteger64 = BigInteger64([1]);
+}